@@ -34,6 +34,23 @@ impl Color {
         }
     }
 
+    /// Creates a new colour from a hex value in the RGB format, like
+    /// `from_rgb_u32`, but returns an error if any bits above `0xffffff`
+    /// are set rather than ignoring them. This catches values that were
+    /// mistakenly written in the RGBA or ARGB format.
+    pub fn try_from_rgb_u32(value: u32) -> anyhow::Result<Color> {
+        if value > 0xffffff {
+            anyhow::bail!("{value:#x} is not a valid RGB value, as it is greater than 0xffffff.");
+        }
+        let [_, red, green, blue] = value.to_be_bytes();
+        Ok(Color {
+            red,
+            green,
+            blue,
+            alpha: 0xff,
+        })
+    }
+
     /// Creates a new colour from a hex value in the RGB format.
     pub fn from_rgb_u32_and_alpha(value: u32, alpha: f32) -> Color {
         let red = (value & 0xff0000) >> 16;
@@ -76,6 +93,30 @@ impl Color {
         }
     }
 
+    /// Creates a new colour from a CSS-style hex string, such as
+    /// `#e4a672`, `e4a672ff` or `#fff`. Short forms are expanded by
+    /// duplicating each digit. Returns `None` if the string is not valid.
+    pub fn from_hex_str(string: &str) -> Option<Color> {
+        let digits = string.strip_prefix('#').unwrap_or(string);
+        if !digits
+            .chars()
+            .all(|character| character.is_ascii_hexdigit())
+        {
+            return None;
+        }
+        let digits: String = match digits.len() {
+            3 | 4 => digits.chars().flat_map(|digit| [digit, digit]).collect(),
+            6 | 8 => digits.to_string(),
+            _ => return None,
+        };
+        let value = u32::from_str_radix(&digits, 16).ok()?;
+        if digits.len() == 8 {
+            Some(Color::from_rgba_u32(value))
+        } else {
+            Some(Color::from_rgb_u32(value))
+        }
+    }
+
     /// Returns the colour as an unsigned integer in an
     /// RGB format.
     pub fn as_rgb_u32(&self) -> u32 {
@@ -110,6 +151,149 @@ impl Color {
         result
     }
 
+    /// Returns the colour as a hex string in the RGBA format,
+    /// including the alpha value.
+    pub fn as_hex_rgba(&self, include_octothorpe: bool) -> String {
+        let mut result = format!("{:08x}", self.as_rgba_u32());
+        if include_octothorpe {
+            result.insert(0, '#');
+        }
+        result
+    }
+
+    /// Returns a copy of the colour with the alpha value replaced.
+    pub fn with_alpha(&self, alpha: u8) -> Color {
+        Color {
+            alpha,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the colour with the colour channels
+    /// multiplied by the alpha value, rounded to the nearest value.
+    pub fn premultiplied(&self) -> Color {
+        let alpha = self.alpha as u32;
+        let premultiply = |value: u8| ((value as u32 * alpha + 127) / 255) as u8;
+        Color {
+            red: premultiply(self.red),
+            green: premultiply(self.green),
+            blue: premultiply(self.blue),
+            alpha: self.alpha,
+        }
+    }
+
+    /// Returns the colour in the palette closest to this colour, measured
+    /// as the squared distance across all four channels. Ties resolve to
+    /// the earliest entry. Returns `None` if the palette is empty.
+    pub fn nearest_in<'a>(&self, palette: &'a [Color]) -> Option<&'a Color> {
+        palette.iter().min_by_key(|color| {
+            let delta = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+            delta(self.red, color.red)
+                + delta(self.green, color.green)
+                + delta(self.blue, color.blue)
+                + delta(self.alpha, color.alpha)
+        })
+    }
+
+    /// Returns whether or not every channel of this colour is within
+    /// `tolerance` of the corresponding channel of another colour.
+    pub fn is_within_tolerance_of(&self, other: &Color, tolerance: u8) -> bool {
+        self.red.abs_diff(other.red) <= tolerance
+            && self.green.abs_diff(other.green) <= tolerance
+            && self.blue.abs_diff(other.blue) <= tolerance
+            && self.alpha.abs_diff(other.alpha) <= tolerance
+    }
+
+    /// Linearly interpolates between this colour and another by `t`,
+    /// which is clamped between 0 and 1. Each channel is interpolated
+    /// independently using straight, rather than premultiplied, alpha
+    /// and rounded to the nearest value.
+    pub fn lerp(&self, other: &Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let lerp_channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color {
+            red: lerp_channel(self.red, other.red),
+            green: lerp_channel(self.green, other.green),
+            blue: lerp_channel(self.blue, other.blue),
+            alpha: lerp_channel(self.alpha, other.alpha),
+        }
+    }
+
+    /// Returns a grey with the same luma as this colour, using the
+    /// Rec. 709 weights of 0.2126, 0.7152 and 0.0722 for red, green
+    /// and blue. The alpha is preserved. These differ from the
+    /// 0.3, 0.59 and 0.11 weights used for luminance by the blend modes.
+    pub fn grayscale(&self) -> Color {
+        let luma =
+            0.2126 * self.red as f32 + 0.7152 * self.green as f32 + 0.0722 * self.blue as f32;
+        let luma = luma.round().clamp(0.0, 255.0) as u8;
+        Color {
+            red: luma,
+            green: luma,
+            blue: luma,
+            alpha: self.alpha,
+        }
+    }
+
+    /// Returns the WCAG contrast ratio between this colour and another,
+    /// from 1 for identical colours up to 21 for black and white.
+    /// The alpha channel is ignored.
+    pub fn contrast_ratio(&self, other: &Color) -> f32 {
+        let luminance = self.relative_luminance();
+        let other_luminance = other.relative_luminance();
+        let lighter = luminance.max(other_luminance);
+        let darker = luminance.min(other_luminance);
+        (lighter + 0.05) / (darker + 0.05)
+    }
+
+    /// Returns the relative luminance of the colour, as defined by WCAG,
+    /// using linearised sRGB values.
+    fn relative_luminance(&self) -> f32 {
+        let (red, green, blue, _) = self.to_linear();
+        0.2126 * red + 0.7152 * green + 0.0722 * blue
+    }
+
+    /// Returns the red, green, blue and alpha components in linear light,
+    /// each in the range 0 to 1. The colour channels are converted
+    /// using the sRGB transfer function, and the alpha is unchanged.
+    pub fn to_linear(&self) -> (f32, f32, f32, f32) {
+        let linearise = |value: u8| {
+            let value = value as f32 / u8::MAX as f32;
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        (
+            linearise(self.red),
+            linearise(self.green),
+            linearise(self.blue),
+            self.alpha as f32 / u8::MAX as f32,
+        )
+    }
+
+    /// Creates a colour from linear light components, each in the
+    /// range 0 to 1. The colour channels are converted using the
+    /// sRGB transfer function. Values outside the range are clamped.
+    pub fn from_linear(red: f32, green: f32, blue: f32, alpha: f32) -> Color {
+        let delinearise = |value: f32| {
+            let value = value.clamp(0.0, 1.0);
+            let value = if value <= 0.0031308 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1.0 / 2.4) - 0.055
+            };
+            (value * u8::MAX as f32).round() as u8
+        };
+        Color {
+            red: delinearise(red),
+            green: delinearise(green),
+            blue: delinearise(blue),
+            alpha: (alpha.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8,
+        }
+    }
+
     /// Creates a colour from HSB values, each provided in the
     /// range between 0 and 1.
     pub fn from_hsb(hue: f32, saturation: f32, brightness: f32) -> Self {
@@ -238,6 +422,64 @@ impl Color {
         self.green = new_color.green;
         self.blue = new_color.blue;
     }
+
+    /// Returns the colour with its hue, saturation and brightness shifted
+    /// by the given deltas. All three are applied in floating point in a
+    /// single step, so the result is only rounded once. The hue wraps
+    /// around, while the saturation and brightness are clamped between
+    /// 0 and 1. The alpha is preserved.
+    ///
+    /// Calling `set_hue`, `set_saturation` and `set_brightness` repeatedly
+    /// rounds the colour to 8 bits each time, so the error builds up.
+    /// For interactive adjustments, keep the original colour and apply
+    /// the total delta to it with this method instead.
+    pub fn adjusted_hsb(
+        &self,
+        hue_delta: f32,
+        saturation_delta: f32,
+        brightness_delta: f32,
+    ) -> Color {
+        let hue = (self.hue() + hue_delta).rem_euclid(1.0);
+        let saturation = (self.saturation() + saturation_delta).clamp(0.0, 1.0);
+        let brightness = (self.brightness() + brightness_delta).clamp(0.0, 1.0);
+        let new_color = Color::from_hsb(hue, saturation, brightness);
+        new_color.with_alpha(self.alpha)
+    }
+
+    /// Creates a colour from HSL values, each provided in the
+    /// range between 0 and 1. The hue matches the one returned
+    /// by `hue()`.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let a = saturation * f32::min(lightness, 1.0 - lightness);
+        let k = |n: f32| (n + hue * 12.0) % 12.0;
+        let f = |n: f32| {
+            lightness - a * f32::max(-1.0, f32::min(k(n) - 3.0, f32::min(9.0 - k(n), 1.0)))
+        };
+
+        let red = (255.0 * f(0.0)).round() as u8;
+        let green = (255.0 * f(8.0)).round() as u8;
+        let blue = (255.0 * f(4.0)).round() as u8;
+
+        Self {
+            red,
+            green,
+            blue,
+            alpha: 0xff,
+        }
+    }
+
+    /// Returns the hue, saturation and lightness of a colour,
+    /// each in the range 0 to 1.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let max = self.red.max(self.green).max(self.blue) as f32 / u8::MAX as f32;
+        let min = self.red.min(self.green).min(self.blue) as f32 / u8::MAX as f32;
+        let lightness = (max + min) / 2.0;
+        if max == min {
+            return (0.0, 0.0, lightness);
+        }
+        let saturation = (max - min) / (1.0 - (2.0 * lightness - 1.0).abs());
+        (self.hue(), saturation, lightness)
+    }
 }
 
 // INTO
@@ -393,6 +635,18 @@ mod tests {
         assert_eq!(color.alpha, 0xff);
     }
 
+    #[test]
+    fn test_try_from_rgb_u32() {
+        let color = Color::try_from_rgb_u32(0xe4a672).unwrap();
+        assert_eq!(color, Color::from_rgb_u32(0xe4a672));
+        assert_eq!(Color::try_from_rgb_u32(0).unwrap(), Color::BLACK);
+        assert_eq!(Color::try_from_rgb_u32(0xffffff).unwrap(), Color::WHITE);
+
+        assert!(Color::try_from_rgb_u32(0x11223344).is_err());
+        assert!(Color::try_from_rgb_u32(0x1000000).is_err());
+        assert!(Color::try_from_rgb_u32(u32::MAX).is_err());
+    }
+
     #[test]
     fn test_from_rgba_u32() {
         let value: u32 = 0xe4a672ff;
@@ -486,4 +740,188 @@ mod tests {
         assert_eq!(color.as_hex(false), "e4a672".to_string());
         assert_eq!(color.as_hex(true), "#e4a672".to_string());
     }
+
+    #[test]
+    fn from_hex_str() {
+        let expected_color = Color::from_rgb_u32(0xe4a672);
+        assert_eq!(Color::from_hex_str("#e4a672"), Some(expected_color.clone()));
+        assert_eq!(Color::from_hex_str("e4a672"), Some(expected_color.clone()));
+        assert_eq!(
+            Color::from_hex_str("#E4A672ff"),
+            Some(expected_color.clone())
+        );
+        assert_eq!(
+            Color::from_hex_str("#e4a67280"),
+            Some(Color::from_rgba_u32(0xe4a67280))
+        );
+        assert_eq!(Color::from_hex_str("#fff"), Some(Color::WHITE));
+        assert_eq!(
+            Color::from_hex_str("#f0a8"),
+            Some(Color::from_rgba_u32(0xff00aa88))
+        );
+        assert_eq!(
+            Color::from_hex_str(&expected_color.as_hex(true)),
+            Some(expected_color)
+        );
+
+        assert_eq!(Color::from_hex_str(" #e4a672"), None);
+        assert_eq!(Color::from_hex_str("#e4a67"), None);
+        assert_eq!(Color::from_hex_str("#e4a67g"), None);
+        assert_eq!(Color::from_hex_str("#+4a672"), None);
+        assert_eq!(Color::from_hex_str(""), None);
+    }
+
+    #[test]
+    fn test_as_hex_rgba() {
+        let color = Color::from_rgba_u32(0xe4a67280);
+        assert_eq!(color.as_hex_rgba(false), "e4a67280".to_string());
+        assert_eq!(color.as_hex_rgba(true), "#e4a67280".to_string());
+
+        let color = Color {
+            red: 0,
+            green: 5,
+            blue: 0,
+            alpha: 9,
+        };
+        assert_eq!(color.as_hex_rgba(false), "00050009".to_string());
+        assert_eq!(color.as_hex(false), "000500".to_string());
+    }
+
+    #[test]
+    fn lerp() {
+        let start = Color::from_rgba_u32(0x00ff4010);
+        let end = Color::from_rgba_u32(0xff008181);
+
+        assert_eq!(start.lerp(&end, 0.0), start);
+        assert_eq!(start.lerp(&end, 1.0), end);
+        assert_eq!(start.lerp(&end, -2.0), start);
+        assert_eq!(start.lerp(&end, 3.0), end);
+
+        // Halfway values are rounded up, so the result is the same
+        // in either direction.
+        assert_eq!(start.lerp(&end, 0.5), Color::from_rgba_u32(0x80806149));
+        assert_eq!(end.lerp(&start, 0.5), Color::from_rgba_u32(0x80806149));
+    }
+
+    #[test]
+    fn adjusted_hsb() {
+        let original = Color::from_rgba_u32(0xe4a67280);
+        let single_step = original.adjusted_hsb(0.5, 0.0, 0.0);
+        assert_eq!(single_step.alpha, 0x80);
+        assert!((single_step.hue() - (original.hue() + 0.5).rem_euclid(1.0)).abs() < 0.01);
+
+        let mut stepped = original.clone();
+        let mut set_repeatedly = original.clone();
+        for step in 1..=100 {
+            stepped = original.adjusted_hsb(step as f32 * 0.005, 0.0, 0.0);
+            set_repeatedly.set_hue(set_repeatedly.hue() + 0.005);
+        }
+        assert_eq!(stepped, single_step);
+        assert!(!set_repeatedly.is_within_tolerance_of(&single_step, 1));
+
+        let clamped = original.adjusted_hsb(0.0, 2.0, -2.0);
+        assert_eq!(clamped, Color::from_rgba_u32(0x00000080));
+    }
+
+    #[test]
+    fn hsl() {
+        let color = Color::from_rgb_u32(0x3366cc);
+        let (hue, saturation, lightness) = color.to_hsl();
+        assert!((hue - 220.0 / 360.0).abs() < 0.0001);
+        assert!((saturation - 0.6).abs() < 0.0001);
+        assert!((lightness - 0.5).abs() < 0.0001);
+        assert_eq!(Color::from_hsl(220.0 / 360.0, 0.6, 0.5), color);
+        assert_eq!(hue, color.hue());
+
+        for value in [0xe4a672, 0x3366cc, 0x808080, 0xb1d2ff, 0x000000, 0xffffff] {
+            let color = Color::from_rgb_u32(value);
+            let (hue, saturation, lightness) = color.to_hsl();
+            assert_eq!(Color::from_hsl(hue, saturation, lightness), color);
+        }
+
+        let grey = Color::from_rgb_u32(0x808080);
+        let (_, saturation, _) = grey.to_hsl();
+        assert_eq!(saturation, 0.0);
+
+        for hue in [0.0, 0.3, 0.75] {
+            assert_eq!(Color::from_hsl(hue, 1.0, 0.0), Color::BLACK);
+            assert_eq!(Color::from_hsl(hue, 1.0, 1.0), Color::WHITE);
+        }
+    }
+
+    #[test]
+    fn with_alpha() {
+        let color = Color::RED.with_alpha(0x80);
+        assert_eq!(color, Color::from_rgba_u32(0xff000080));
+        assert_eq!(Color::RED.alpha, 0xff);
+    }
+
+    #[test]
+    fn premultiplied() {
+        let color = Color::from_rgba_u32(0xe4a67280);
+        assert_eq!(color.premultiplied(), Color::from_rgba_u32(0x72533980));
+        assert_eq!(Color::WHITE.premultiplied(), Color::WHITE);
+        let clear = Color::from_rgba_u32(0xe4a67200);
+        assert_eq!(clear.premultiplied(), Color::CLEAR);
+    }
+
+    #[test]
+    fn contrast_ratio() {
+        assert!((Color::BLACK.contrast_ratio(&Color::WHITE) - 21.0).abs() < 0.001);
+        assert!((Color::WHITE.contrast_ratio(&Color::BLACK) - 21.0).abs() < 0.001);
+        let color = Color::from_rgb_u32(0xe4a672);
+        assert_eq!(color.contrast_ratio(&color), 1.0);
+        let ratio = Color::from_rgb_u32(0x777777).contrast_ratio(&Color::WHITE);
+        assert!((ratio - 4.48).abs() < 0.01);
+    }
+
+    #[test]
+    fn linear() {
+        for value in 0..=u8::MAX {
+            let color = Color {
+                red: value,
+                green: value / 2,
+                blue: u8::MAX - value,
+                alpha: value,
+            };
+            let (red, green, blue, alpha) = color.to_linear();
+            let result = Color::from_linear(red, green, blue, alpha);
+            assert!(result.is_within_tolerance_of(&color, 1));
+            assert_eq!(result.alpha, color.alpha);
+        }
+
+        let (red, green, blue, alpha) = Color::from_rgb_u32(0x808080).to_linear();
+        assert!(red < 0.5);
+        assert_eq!(red, green);
+        assert_eq!(red, blue);
+        assert_eq!(alpha, 1.0);
+
+        let clamped = Color::from_linear(1.5, -0.5, 0.0, 2.0);
+        assert_eq!(clamped, Color::from_rgba_u32(0xff0000ff));
+    }
+
+    #[test]
+    fn nearest_in() {
+        let palette = [Color::BLACK, Color::RED, Color::GREEN, Color::BLUE];
+        let reddish = Color::from_rgb_u32(0xc83c28);
+        assert_eq!(reddish.nearest_in(&palette), Some(&Color::RED));
+        assert_eq!(reddish.nearest_in(&[]), None);
+
+        // Both entries are the same distance away, so the first wins.
+        let purple = Color::from_rgb_u32(0x800080);
+        let palette = [Color::BLUE, Color::RED];
+        assert_eq!(purple.nearest_in(&palette), Some(&Color::BLUE));
+    }
+
+    #[test]
+    fn grayscale() {
+        assert_eq!(Color::RED.grayscale(), Color::from_rgb_u32(0x363636));
+        assert_eq!(Color::GREEN.grayscale(), Color::from_rgb_u32(0xb6b6b6));
+        assert_eq!(Color::WHITE.grayscale(), Color::WHITE);
+        let translucent_blue = Color::BLUE.with_alpha(0x40);
+        assert_eq!(
+            translucent_blue.grayscale(),
+            Color::from_rgba_u32(0x12121240)
+        );
+    }
 }
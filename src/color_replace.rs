@@ -5,25 +5,97 @@ use crate::{
     BlendMode, Color, Image, Mask, Point, Rect,
 };
 
+/// Which neighbouring pixels a flood fill spreads to.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Spreads to the pixels above, below, left and right.
+    #[default]
+    Four,
+    /// Spreads to the diagonal pixels as well.
+    Eight,
+}
+
+/// Controls which pixels a flood fill spreads to.
+#[derive(Default, Debug, Clone, Copy)]
+struct FillOptions {
+    /// Pixels are filled if each of their channels is within
+    /// this of the starting pixel’s colour.
+    tolerance: u8,
+    /// Which neighbouring pixels the fill spreads to.
+    connectivity: Connectivity,
+}
+
 /// Replaces all instances of one colour with another.
 pub fn replace_color(image: &mut Image, target_color: &Color, replacement_color: &Color) {
-    let target_color: [u8; 4] = target_color.into();
+    replace_color_with_tolerance(image, target_color, replacement_color, 0);
+}
+
+/// Replaces every pixel that is close to one colour with another. A pixel
+/// matches when each of its channels, including alpha, differs from the
+/// corresponding channel of the target colour by at most `tolerance`,
+/// as with `Color::is_within_tolerance_of`. This allows for noise in
+/// photographic sources, and a tolerance of 0 only matches exact colours.
+pub fn replace_color_with_tolerance(
+    image: &mut Image,
+    target_color: &Color,
+    replacement_color: &Color,
+    tolerance: u8,
+) {
+    let replacement: [u8; 4] = replacement_color.into();
     for y in 0..image.size.height {
         let offset = (y * image.bytes_per_row) as usize;
         for x in (0..image.size.width * 4).step_by(4) {
             let start = offset + x as usize;
-            let data = image.data.get(start..(start + 4)).unwrap();
+            let data: [u8; 4] = image.data[start..(start + 4)].try_into().unwrap();
 
-            if data == target_color {
-                image.data[start + 0] = replacement_color.red;
-                image.data[start + 1] = replacement_color.green;
-                image.data[start + 2] = replacement_color.blue;
-                image.data[start + 3] = replacement_color.alpha;
+            if Color::from(data).is_within_tolerance_of(target_color, tolerance) {
+                image.data[start..(start + 4)].copy_from_slice(&replacement);
             }
         }
     }
 }
 
+/// Recolours every pixel in the image whose channels are each within
+/// `tolerance` of the colour at the starting point, whether or not it
+/// is connected to the starting point. Returns the area affected.
+pub fn global_fill(
+    image: &mut Image,
+    start: Point<i32>,
+    fill_color: &Color,
+    tolerance: u8,
+) -> anyhow::Result<Rect<i32>> {
+    if start.x < 0
+        || start.y < 0
+        || start.x >= image.size.width as i32
+        || start.y >= image.size.height as i32
+    {
+        anyhow::bail!("Point outside of image bounds.");
+    }
+    let target_color =
+        Color::from_rgba_u32(unsigned_int_color(start, &image.data, image.bytes_per_row));
+    let new_color: [u8; 4] = fill_color.into();
+
+    let mut affected_region: Option<Rect<i32>> = None;
+    for y in 0..image.size.height as i32 {
+        let offset = y as usize * image.bytes_per_row as usize;
+        for x in 0..image.size.width as i32 {
+            let start = offset + x as usize * 4;
+            let data: [u8; 4] = image.data[start..(start + 4)].try_into().unwrap();
+            if !Color::from(data).is_within_tolerance_of(&target_color, tolerance) {
+                continue;
+            }
+            image.data[start..(start + 4)].copy_from_slice(&new_color);
+            let pixel_rect = Rect::new(x, y, 1, 1);
+            affected_region = Some(match affected_region {
+                Some(region) => region.union(&pixel_rect),
+                None => pixel_rect,
+            });
+        }
+    }
+
+    Ok(affected_region.unwrap_or(Rect::new(start.x, start.y, 1, 1)))
+}
+
 /// Returns an image the same size as the source image
 /// where any corresponding pixels of the target colour
 /// in the source image are output as black, and all other
@@ -54,14 +126,22 @@ pub fn mask_image(source_image: &Image, target_color: &Color) -> Image {
 /// Returns the area affected by the flood fill.
 /// If the `secondary_image` is supplied, this will also
 /// be recocoloured, but not referenced when computing the
-/// area to be filled.
+/// area to be filled. If `filled_points` is supplied, every
+/// recoloured pixel is added to it.
 fn flood_fill_in_bounds(
     image: &mut Image,
     start: Point<i32>,
     fill_color: &Color,
     secondary_image: Option<&mut Image>,
     bounding_box: Option<Rect<i32>>,
+    options: FillOptions,
+    mut filled_points: Option<&mut Vec<Point<i32>>>,
 ) -> anyhow::Result<Rect<i32>> {
+    let FillOptions {
+        tolerance,
+        connectivity,
+    } = options;
+
     let image_bounds = Rect {
         origin: Point::zero(),
         size: image.size.into(),
@@ -79,6 +159,10 @@ fn flood_fill_in_bounds(
         anyhow::bail!("Point outside of image bounds.");
     }
 
+    // Validate the buffers up front, so that an error can never leave a
+    // fill half applied.
+    validate_buffer(image)?;
+
     let min_x = bounding_box.min_x();
     let max_x = bounding_box.max_x();
     let min_y = bounding_box.min_y();
@@ -98,6 +182,7 @@ fn flood_fill_in_bounds(
         {
             anyhow::bail!("The secondary image’s properties do not match the primary’s.")
         }
+        validate_buffer(secondary_image)?;
         &mut secondary_image.data
     } else {
         &mut some
@@ -113,50 +198,78 @@ fn flood_fill_in_bounds(
     // Target colour is the colour we want to replace.
     let target_color = unsigned_int_color(start, vertex_buffer, bytes_per_row);
     let new_color = fill_color.as_rgba_u32();
+    if tolerance == 0 && target_color == new_color {
+        return Ok(Rect::new(start.x, start.y, 1, 1));
+    }
+
+    // With a tolerance, filled pixels can still match the target colour,
+    // so they need to be tracked to stop them from being filled again.
+    let width = image.size.width as usize;
+    let mut filled = if tolerance > 0 {
+        vec![false; width * image.size.height as usize]
+    } else {
+        Vec::new()
+    };
+    let target = Color::from_rgba_u32(target_color);
+    let matches = |point: Point<i32>, vertex_buffer: &Vec<u8>, filled: &[bool]| {
+        let color = unsigned_int_color(point, vertex_buffer, bytes_per_row);
+        if tolerance == 0 {
+            return color == target_color;
+        }
+        !filled[point.y as usize * width + point.x as usize]
+            && Color::from_rgba_u32(color).is_within_tolerance_of(&target, tolerance)
+    };
+
+    // Pushes a neighbouring point when it starts a new run of matching
+    // pixels on one side of the current column.
+    let check_neighbour = |point: Point<i32>,
+                           span: &mut bool,
+                           points: &mut Vec<Point<i32>>,
+                           vertex_buffer: &Vec<u8>,
+                           filled: &[bool]| {
+        let is_match = matches(point, vertex_buffer, filled);
+        if !*span && is_match {
+            points.push(point);
+            *span = true;
+        } else if *span && !is_match {
+            *span = false;
+        }
+    };
 
     let mut points: Vec<Point<i32>> = Vec::new();
     points.push(start);
 
-    let mut color: u32;
     let mut span_left;
     let mut span_right;
 
-    while !points.is_empty() {
-        let Some(mut current_point) = points.pop() else {
-            continue;
-        };
-        color = unsigned_int_color(current_point, vertex_buffer, bytes_per_row);
-
-        while current_point.y >= min_y && color == target_color {
+    while let Some(mut current_point) = points.pop() {
+        while current_point.y >= min_y && matches(current_point, vertex_buffer, &filled) {
             current_point.y -= 1;
-
-            if current_point.y >= min_y {
-                color = unsigned_int_color(current_point, vertex_buffer, bytes_per_row);
-            }
         }
 
         current_point.y += 1;
+        let span_top = current_point.y;
 
         span_left = false;
         span_right = false;
 
-        color = unsigned_int_color(current_point, vertex_buffer, bytes_per_row);
-
-        while current_point.y < max_y && color == target_color && new_color != color {
+        while current_point.y < max_y && matches(current_point, vertex_buffer, &filled) {
             // Change the old colour to the new colour’s RGBA value.
             let byte_index =
                 bytes_per_row as usize * current_point.y as usize + current_point.x as usize * 4;
 
-            vertex_buffer[byte_index + 0] = ((0xff000000 & new_color) >> 24) as u8;
-            vertex_buffer[byte_index + 1] = ((0x00ff0000 & new_color) >> 16) as u8;
-            vertex_buffer[byte_index + 2] = ((0x0000ff00 & new_color) >> 8) as u8;
-            vertex_buffer[byte_index + 3] = (0x000000ff & new_color) as u8;
+            write_unsigned_int_color(new_color, byte_index, vertex_buffer)?;
+            if let Some(filled) =
+                filled.get_mut(current_point.y as usize * width + current_point.x as usize)
+            {
+                *filled = true;
+            }
 
             if has_secondary_image {
-                secondary_vertex_buffer[byte_index + 0] = ((0xff000000 & new_color) >> 24) as u8;
-                secondary_vertex_buffer[byte_index + 1] = ((0x00ff0000 & new_color) >> 16) as u8;
-                secondary_vertex_buffer[byte_index + 2] = ((0x0000ff00 & new_color) >> 8) as u8;
-                secondary_vertex_buffer[byte_index + 3] = (0x000000ff & new_color) as u8;
+                write_unsigned_int_color(new_color, byte_index, secondary_vertex_buffer)?;
+            }
+            if let Some(filled_points) = filled_points.as_deref_mut() {
+                filled_points.push(current_point);
             }
 
             if current_point.x > min_x {
@@ -164,15 +277,13 @@ fn flood_fill_in_bounds(
                     x: current_point.x - 1,
                     y: current_point.y,
                 };
-
-                color = unsigned_int_color(west_point, &vertex_buffer, bytes_per_row);
-
-                if !span_left && color == target_color {
-                    points.push(west_point);
-                    span_left = true;
-                } else if span_left && color != target_color {
-                    span_left = false;
-                }
+                check_neighbour(
+                    west_point,
+                    &mut span_left,
+                    &mut points,
+                    vertex_buffer,
+                    &filled,
+                );
             }
 
             if current_point.x < (max_x - 1) {
@@ -180,15 +291,13 @@ fn flood_fill_in_bounds(
                     x: current_point.x + 1,
                     y: current_point.y,
                 };
-
-                color = unsigned_int_color(east_point, &vertex_buffer, bytes_per_row);
-
-                if !span_right && color == target_color {
-                    points.push(east_point);
-                    span_right = true;
-                } else if span_right && color != target_color {
-                    span_right = false;
-                }
+                check_neighbour(
+                    east_point,
+                    &mut span_right,
+                    &mut points,
+                    vertex_buffer,
+                    &filled,
+                );
             }
 
             if !span_right || !span_left {
@@ -199,9 +308,27 @@ fn flood_fill_in_bounds(
             }
 
             current_point.y += 1;
+        }
 
-            if current_point.y < max_y {
-                color = unsigned_int_color(current_point, &vertex_buffer, bytes_per_row);
+        // With eight-way connectivity, the pixels diagonally above and
+        // below the ends of the column are neighbours too.
+        if connectivity == Connectivity::Eight && current_point.y > span_top {
+            let diagonal_rows = [span_top - 1, current_point.y]
+                .into_iter()
+                .filter(|y| *y >= min_y && *y < max_y);
+            for y in diagonal_rows {
+                for x in [current_point.x - 1, current_point.x + 1] {
+                    if x < min_x || x >= max_x {
+                        continue;
+                    }
+                    check_neighbour(
+                        Point { x, y },
+                        &mut false,
+                        &mut points,
+                        vertex_buffer,
+                        &filled,
+                    );
+                }
             }
         }
     }
@@ -223,7 +350,71 @@ pub fn flood_fill(
     start: Point<i32>,
     fill_color: &Color,
 ) -> anyhow::Result<Rect<i32>> {
-    flood_fill_in_bounds(image, start, fill_color, None, None)
+    flood_fill_in_bounds(
+        image,
+        start,
+        fill_color,
+        None,
+        None,
+        FillOptions::default(),
+        None,
+    )
+}
+
+/// Fills the selected colour from the starting point to all
+/// pixels the same colour as the starting point, and
+/// returns every pixel that was recoloured, in no particular
+/// order. This allows the fill to be undone exactly.
+pub fn flood_fill_collecting(
+    image: &mut Image,
+    start: Point<i32>,
+    fill_color: &Color,
+) -> anyhow::Result<Vec<Point<i32>>> {
+    let mut filled_points = Vec::new();
+    flood_fill_in_bounds(
+        image,
+        start,
+        fill_color,
+        None,
+        None,
+        FillOptions::default(),
+        Some(&mut filled_points),
+    )?;
+    Ok(filled_points)
+}
+
+/// Fills the selected colour from the starting point to all
+/// pixels the same colour as the starting point that are
+/// connected to it. With `Connectivity::Eight`, the fill also
+/// spreads diagonally, so it passes through diagonal lines.
+pub fn flood_fill_connected(
+    image: &mut Image,
+    start: Point<i32>,
+    fill_color: &Color,
+    connectivity: Connectivity,
+) -> anyhow::Result<Rect<i32>> {
+    let options = FillOptions {
+        connectivity,
+        ..FillOptions::default()
+    };
+    flood_fill_in_bounds(image, start, fill_color, None, None, options, None)
+}
+
+/// Fills the selected colour from the starting point to all
+/// pixels whose channels are each within `tolerance` of the
+/// starting point’s colour, so that anti-aliased edges are
+/// filled too. A tolerance of 255 fills the whole image.
+pub fn flood_fill_with_tolerance(
+    image: &mut Image,
+    start: Point<i32>,
+    fill_color: &Color,
+    tolerance: u8,
+) -> anyhow::Result<Rect<i32>> {
+    let options = FillOptions {
+        tolerance,
+        ..FillOptions::default()
+    };
+    flood_fill_in_bounds(image, start, fill_color, None, None, options, None)
 }
 
 /// Fills the selected colour from the starting point to all
@@ -237,15 +428,24 @@ pub fn flood_fill_with_mask(
 ) -> anyhow::Result<Rect<i32>> {
     let bounding_box = Some(mask.bounding_box());
     let mut result = image.clone();
-    let affected_region = flood_fill_in_bounds(&mut result, start, fill_color, None, bounding_box)?;
+    let affected_region = flood_fill_in_bounds(
+        &mut result,
+        start,
+        fill_color,
+        None,
+        bounding_box,
+        FillOptions::default(),
+        None,
+    )?;
     if fill_color.alpha == 0 {
         // For a clear, erase the masked area,
         // then just draw the two images on top of each other.
+        // The original is replaced by the result, so the mask can be
+        // erased from it in place rather than from another copy.
         let mut layer = Layer::new(&mask.image(), mask.bounding_box().origin.into());
         layer.blend_mode = BlendMode::DestinationOut;
-        let mut image_with_mask_erased = image.clone();
-        composite::draw_layer_over_image(&mut image_with_mask_erased, &layer);
-        let layer = Layer::new(&image_with_mask_erased, Point::zero());
+        composite::draw_layer_over_image(image, &layer);
+        let layer = Layer::new(image, Point::zero());
         composite::draw_layer_over_image(&mut result, &layer);
         *image = result;
     } else {
@@ -271,13 +471,274 @@ pub fn flood_fill_with_reference(
         fill_color,
         Some(target_image),
         None,
+        FillOptions::default(),
+        None,
     )?;
     Ok(affected_region)
 }
 
+/// Fills the selected colour from the starting point to all
+/// pixels the same colour as the starting point, treating
+/// gaps of up to `max_gap` pixels in the surrounding boundary
+/// as closed so that the fill doesn’t leak through them.
+pub fn flood_fill_with_gap_closing(
+    image: &mut Image,
+    start: Point<i32>,
+    fill_color: &Color,
+    max_gap: u32,
+) -> anyhow::Result<Rect<i32>> {
+    let image_bounds = Rect {
+        origin: Point::zero(),
+        size: image.size.into(),
+    };
+    if start.x < 0
+        || start.y < 0
+        || start.x >= image_bounds.max_x()
+        || start.y >= image_bounds.max_y()
+    {
+        anyhow::bail!("Point outside of image bounds.");
+    }
+
+    let width = image.size.width as usize;
+    let height = image.size.height as usize;
+    let target_color = unsigned_int_color(start, &image.data, image.bytes_per_row);
+    let is_target: Vec<bool> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let point = Point {
+                x: x as i32,
+                y: y as i32,
+            };
+            unsigned_int_color(point, &image.data, image.bytes_per_row) == target_color
+        })
+        .collect();
+
+    // Thicken the boundary so that any gap narrower than `max_gap` is closed.
+    let radius = max_gap.div_ceil(2) as usize;
+    let is_boundary: Vec<bool> = is_target.iter().map(|is_target| !is_target).collect();
+    let is_blocked = dilate(&is_boundary, width, height, radius);
+
+    let start_index = start.y as usize * width + start.x as usize;
+    let mut is_filled = vec![false; is_target.len()];
+    is_filled[start_index] = true;
+    let mut points = vec![start_index];
+    while let Some(index) = points.pop() {
+        let x = index % width;
+        let y = index / width;
+        let neighbours = [
+            (x > 0).then(|| index - 1),
+            (x + 1 < width).then(|| index + 1),
+            (y > 0).then(|| index - width),
+            (y + 1 < height).then(|| index + width),
+        ];
+        for neighbour in neighbours.into_iter().flatten() {
+            if !is_filled[neighbour] && !is_blocked[neighbour] {
+                is_filled[neighbour] = true;
+                points.push(neighbour);
+            }
+        }
+    }
+
+    // Grow the fill back out by the same amount to reach the pixels
+    // alongside the boundary that were covered by the thickening.
+    let is_filled = dilate(&is_filled, width, height, radius);
+
+    let mut affected_region: Option<Rect<i32>> = None;
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            if !is_filled[index] || !is_target[index] {
+                continue;
+            }
+            let location = Point {
+                x: x as i32,
+                y: y as i32,
+            };
+            image.set_pixel_color(fill_color.clone(), location.into());
+            let pixel_rect = Rect::new(location.x, location.y, 1, 1);
+            affected_region = Some(match affected_region {
+                Some(region) => region.union(&pixel_rect),
+                None => pixel_rect,
+            });
+        }
+    }
+
+    Ok(affected_region.unwrap_or(Rect::new(start.x, start.y, 1, 1)))
+}
+
+/// Fills the selected colour from the starting point to all
+/// pixels the same colour as the starting point, using
+/// multiple threads. The result is identical to `flood_fill`.
+///
+/// The image is split into horizontal bands whose connected
+/// regions are found in parallel, then the regions that touch
+/// across the seams between bands are merged.
+#[cfg(feature = "rayon")]
+pub fn flood_fill_parallel(
+    image: &mut Image,
+    start: Point<i32>,
+    fill_color: &Color,
+) -> anyhow::Result<Rect<i32>> {
+    use rayon::prelude::*;
+    use std::collections::{HashMap, HashSet};
+
+    let image_bounds = Rect {
+        origin: Point::zero(),
+        size: image.size.into(),
+    };
+    if start.x < 0
+        || start.y < 0
+        || start.x >= image_bounds.max_x()
+        || start.y >= image_bounds.max_y()
+    {
+        anyhow::bail!("Point outside of image bounds.");
+    }
+    validate_buffer(image)?;
+
+    let target_color = unsigned_int_color(start, &image.data, image.bytes_per_row);
+    let new_color = fill_color.as_rgba_u32();
+    if target_color == new_color {
+        return Ok(Rect::new(start.x, start.y, 1, 1));
+    }
+
+    let width = image.size.width as usize;
+    let height = image.size.height as usize;
+    let bytes_per_row = image.bytes_per_row as usize;
+    let band_height = height.div_ceil(rayon::current_num_threads()).max(1);
+
+    // Label the regions within each band. Each region is labelled with
+    // the index of its first pixel, so labels are unique across bands.
+    const UNLABELLED: usize = usize::MAX;
+    let mut labels = vec![UNLABELLED; width * height];
+    let image_data = &image.data;
+    labels
+        .par_chunks_mut(width * band_height)
+        .enumerate()
+        .for_each(|(band, band_labels)| {
+            let band_start = band * band_height * width;
+            let rows = band_labels.len() / width;
+            let is_target = |index: usize| {
+                let point = Point {
+                    x: ((band_start + index) % width) as i32,
+                    y: ((band_start + index) / width) as i32,
+                };
+                unsigned_int_color(point, image_data, bytes_per_row as u32) == target_color
+            };
+            let mut points = Vec::new();
+            for index in 0..band_labels.len() {
+                if band_labels[index] != UNLABELLED || !is_target(index) {
+                    continue;
+                }
+                let label = band_start + index;
+                band_labels[index] = label;
+                points.push(index);
+                while let Some(index) = points.pop() {
+                    let x = index % width;
+                    let y = index / width;
+                    let neighbours = [
+                        (x > 0).then(|| index - 1),
+                        (x + 1 < width).then(|| index + 1),
+                        (y > 0).then(|| index - width),
+                        (y + 1 < rows).then(|| index + width),
+                    ];
+                    for neighbour in neighbours.into_iter().flatten() {
+                        if band_labels[neighbour] == UNLABELLED && is_target(neighbour) {
+                            band_labels[neighbour] = label;
+                            points.push(neighbour);
+                        }
+                    }
+                }
+            }
+        });
+
+    // Merge the regions that touch across the seams between bands.
+    let mut parents: HashMap<usize, usize> = HashMap::new();
+    fn find(parents: &mut HashMap<usize, usize>, label: usize) -> usize {
+        let parent = *parents.entry(label).or_insert(label);
+        if parent == label {
+            return label;
+        }
+        let root = find(parents, parent);
+        parents.insert(label, root);
+        root
+    }
+    for seam_y in (band_height..height).step_by(band_height) {
+        for x in 0..width {
+            let above = labels[(seam_y - 1) * width + x];
+            let below = labels[seam_y * width + x];
+            if above == UNLABELLED || below == UNLABELLED {
+                continue;
+            }
+            let above_root = find(&mut parents, above);
+            let below_root = find(&mut parents, below);
+            if above_root != below_root {
+                parents.insert(above_root, below_root);
+            }
+        }
+    }
+    let start_label = labels[start.y as usize * width + start.x as usize];
+    let start_root = find(&mut parents, start_label);
+    let mut fill_labels: HashSet<usize> = HashSet::from([start_label]);
+    let merged_labels: Vec<usize> = parents.keys().copied().collect();
+    for label in merged_labels {
+        if find(&mut parents, label) == start_root {
+            fill_labels.insert(label);
+        }
+    }
+
+    // Recolour the pixels in the filled regions.
+    let fill_bytes = new_color.to_be_bytes();
+    let affected_regions: Vec<Option<Rect<i32>>> = image
+        .data
+        .par_chunks_mut(bytes_per_row * band_height)
+        .zip(labels.par_chunks(width * band_height))
+        .enumerate()
+        .map(|(band, (band_data, band_labels))| {
+            let mut affected_region: Option<Rect<i32>> = None;
+            for (index, label) in band_labels.iter().enumerate() {
+                if !fill_labels.contains(label) {
+                    continue;
+                }
+                let x = index % width;
+                let y = index / width;
+                let offset = y * bytes_per_row + x * 4;
+                band_data[offset..offset + 4].copy_from_slice(&fill_bytes);
+                let pixel_rect = Rect::new(x as i32, (band * band_height + y) as i32, 1, 1);
+                affected_region = Some(match affected_region {
+                    Some(region) => region.union(&pixel_rect),
+                    None => pixel_rect,
+                });
+            }
+            affected_region
+        })
+        .collect();
+
+    let affected_region = affected_regions
+        .into_iter()
+        .flatten()
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or(Rect::new(start.x, start.y, 1, 1));
+    Ok(affected_region)
+}
+
 // MARK: Helper methods
 
 /// Helper method for the bucket fill that returns an array for the colour at a point.
+/// Checks that an image’s data is large enough for every pixel to be
+/// read and written, given its size and bytes per row.
+fn validate_buffer(image: &Image) -> anyhow::Result<()> {
+    if (image.bytes_per_row as usize) < image.size.width as usize * 4 {
+        anyhow::bail!("The image’s bytes per row is too small for its width.");
+    }
+    let required_length = (image.size.height as usize).saturating_sub(1)
+        * image.bytes_per_row as usize
+        + image.size.width as usize * 4;
+    if image.data.len() < required_length {
+        anyhow::bail!("The image’s data is too short for its size.");
+    }
+    Ok(())
+}
+
 fn unsigned_int_color(point: Point<i32>, vertex_buffer: &Vec<u8>, bytes_per_row: u32) -> u32 {
     let offset = bytes_per_row as usize * point.y as usize + point.x as usize * 4;
 
@@ -293,6 +754,46 @@ fn unsigned_int_color(point: Point<i32>, vertex_buffer: &Vec<u8>, bytes_per_row:
     first_channel << 24 | second_channel << 16 | third_channel << 8 | fourth_channel
 }
 
+/// Expands every set value in a mask by a radius in each direction,
+/// using a square structuring element.
+fn dilate(mask: &[bool], width: usize, height: usize, radius: usize) -> Vec<bool> {
+    if radius == 0 {
+        return mask.to_vec();
+    }
+    let mut horizontal = vec![false; mask.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let start = x.saturating_sub(radius);
+            let end = (x + radius).min(width - 1);
+            horizontal[y * width + x] = (start..=end).any(|x| mask[y * width + x]);
+        }
+    }
+    let mut result = vec![false; mask.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let start = y.saturating_sub(radius);
+            let end = (y + radius).min(height - 1);
+            result[y * width + x] = (start..=end).any(|y| horizontal[y * width + x]);
+        }
+    }
+    result
+}
+
+/// Helper method for the bucket fill that writes a colour to the
+/// buffer at an offset. Returns an error, rather than panicking,
+/// if the offset is outside of the buffer.
+fn write_unsigned_int_color(
+    color: u32,
+    byte_index: usize,
+    vertex_buffer: &mut [u8],
+) -> anyhow::Result<()> {
+    let Some(pixel) = vertex_buffer.get_mut(byte_index..byte_index + 4) else {
+        anyhow::bail!("Pixel offset {byte_index} is outside of the image data.");
+    };
+    pixel.copy_from_slice(&color.to_be_bytes());
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use std::path::PathBuf;
@@ -489,6 +990,151 @@ mod test {
         assert!(result_02.appears_equal_to(&expected_image_02));
     }
 
+    #[test]
+    fn test_flood_fill_collecting() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/red_32.png");
+        let original = Image::open(path).unwrap();
+        let fill_color = Color::from_rgb_u32(0x00ffff);
+        let start = Point { x: 2, y: 5 };
+
+        let mut image = original.clone();
+        let mut filled_points = flood_fill_collecting(&mut image, start, &fill_color).unwrap();
+
+        assert_eq!(filled_points.len(), 1024);
+        filled_points.sort_by_key(|point| (point.y, point.x));
+        filled_points.dedup();
+        assert_eq!(filled_points.len(), 1024);
+        let mut expected = original.clone();
+        flood_fill(&mut expected, start, &fill_color).unwrap();
+        assert!(image == expected);
+
+        // Restoring the collected pixels undoes the fill.
+        for point in filled_points {
+            let color = original.pixel_color(point).unwrap();
+            image.set_pixel_color(color, point.into());
+        }
+        assert!(image == original);
+
+        let mut image = original.clone();
+        let filled_points = flood_fill_collecting(&mut image, start, &Color::RED).unwrap();
+        assert!(filled_points.is_empty());
+    }
+
+    #[test]
+    fn test_global_fill() {
+        let size = Size {
+            width: 10,
+            height: 6,
+        };
+        let mut image = Image::color(&Color::WHITE, size);
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (7, 3), (8, 4)] {
+            image.set_pixel_color(Color::RED, Point { x, y });
+        }
+        image.set_pixel_color(Color::from_rgb_u32(0xf00808), Point { x: 8, y: 3 });
+        image.set_pixel_color(Color::from_rgb_u32(0xc00000), Point { x: 5, y: 5 });
+        let fill_color = Color::BLUE;
+
+        let mut exact = image.clone();
+        let region = global_fill(&mut exact, Point { x: 1, y: 1 }, &fill_color, 0).unwrap();
+        assert_eq!(region, Rect::new(1, 1, 8, 4));
+        let filled_count =
+            |image: &Image| image.pixels().filter(|color| *color == fill_color).count();
+        assert_eq!(filled_count(&exact), 5);
+        assert_eq!(exact.pixel_color(Point { x: 7, y: 3 }), Some(Color::BLUE));
+
+        let region = global_fill(&mut image, Point { x: 7, y: 3 }, &fill_color, 16).unwrap();
+        assert_eq!(region, Rect::new(1, 1, 8, 4));
+        assert_eq!(filled_count(&image), 6);
+        assert_eq!(
+            image.pixel_color(Point { x: 5, y: 5 }),
+            Some(Color::from_rgb_u32(0xc00000))
+        );
+
+        assert!(global_fill(&mut image, Point { x: 10, y: 0 }, &fill_color, 0).is_err());
+    }
+
+    #[test]
+    fn test_flood_fill_with_tolerance() {
+        let size = Size {
+            width: 12,
+            height: 12,
+        };
+        let mut image = Image::color(&Color::WHITE, size);
+        // A black square with an anti-aliased edge.
+        for y in 3..9 {
+            for x in 3..9 {
+                let is_edge = x == 3 || x == 8 || y == 3 || y == 8;
+                let color = if is_edge {
+                    Color::from_rgb_u32(0xe8e8e8)
+                } else {
+                    Color::BLACK
+                };
+                image.set_pixel_color(color, Point { x, y });
+            }
+        }
+        let fill_color = Color::RED;
+        let filled_count = |tolerance: u8| {
+            let mut result = image.clone();
+            let region = flood_fill_with_tolerance(
+                &mut result,
+                Point { x: 0, y: 0 },
+                &fill_color,
+                tolerance,
+            )
+            .unwrap();
+            let count = result.pixels().filter(|color| *color == fill_color).count();
+            (count, region)
+        };
+
+        let whole_image = Rect::new(0, 0, 12, 12);
+        assert_eq!(filled_count(0), (144 - 36, whole_image));
+        assert_eq!(filled_count(32), (144 - 16, whole_image));
+        assert_eq!(filled_count(255), (144, whole_image));
+
+        let mut exact = image.clone();
+        flood_fill(&mut exact, Point { x: 0, y: 0 }, &fill_color).unwrap();
+        let mut with_tolerance = image.clone();
+        flood_fill_with_tolerance(&mut with_tolerance, Point { x: 0, y: 0 }, &fill_color, 0)
+            .unwrap();
+        assert!(exact == with_tolerance);
+    }
+
+    #[test]
+    fn test_flood_fill_connected() {
+        let size = Size {
+            width: 6,
+            height: 6,
+        };
+        let mut image = Image::color(&Color::WHITE, size);
+        for x in 0..6 {
+            image.set_pixel_color(Color::BLACK, Point { x, y: 5 - x });
+        }
+        let fill_color = Color::RED;
+        let filled = |connectivity: Connectivity| {
+            let mut result = image.clone();
+            let region =
+                flood_fill_connected(&mut result, Point { x: 0, y: 0 }, &fill_color, connectivity)
+                    .unwrap();
+            let count = result.pixels().filter(|color| *color == fill_color).count();
+            (result, count, region)
+        };
+
+        let (four, count, region) = filled(Connectivity::Four);
+        assert_eq!(count, 15);
+        assert_eq!(region, Rect::new(0, 0, 5, 5));
+        assert_eq!(four.pixel_color(Point { x: 5, y: 5 }), Some(Color::WHITE));
+        let mut expected = image.clone();
+        flood_fill(&mut expected, Point { x: 0, y: 0 }, &fill_color).unwrap();
+        assert!(four == expected);
+
+        let (eight, count, region) = filled(Connectivity::Eight);
+        assert_eq!(count, 30);
+        assert_eq!(region, Rect::new(0, 0, 6, 6));
+        assert_eq!(eight.pixel_color(Point { x: 5, y: 5 }), Some(Color::RED));
+        assert_eq!(eight.pixel_color(Point { x: 2, y: 3 }), Some(Color::BLACK));
+    }
+
     #[test]
     fn test_replace_color() {
         let bytes = vec![
@@ -519,4 +1165,157 @@ mod test {
 
         assert_eq!(image.data, expected_bytes);
     }
+
+    #[test]
+    fn test_replace_color_with_tolerance() {
+        let target_color = Color::from_rgb_u32(0x540013);
+        let near_match = Color::from_rgb_u32(0x550012);
+        let replacement_color = Color::from_rgb_u32(0xff13ff);
+        let mut image = Image::color(
+            &target_color,
+            Size {
+                width: 2,
+                height: 1,
+            },
+        );
+        image.set_pixel_color(near_match.clone(), Point { x: 1, y: 0 });
+
+        let mut exact = image.clone();
+        replace_color_with_tolerance(&mut exact, &target_color, &replacement_color, 0);
+        assert_eq!(
+            exact.pixel_color(Point { x: 0, y: 0 }),
+            Some(replacement_color.clone())
+        );
+        assert_eq!(exact.pixel_color(Point { x: 1, y: 0 }), Some(near_match));
+
+        replace_color_with_tolerance(&mut image, &target_color, &replacement_color, 1);
+        assert_eq!(
+            image.pixel_color(Point { x: 1, y: 0 }),
+            Some(replacement_color)
+        );
+    }
+
+    #[test]
+    fn test_flood_fill_with_gap_closing() {
+        let mut image = Image::empty(Size {
+            width: 12,
+            height: 12,
+        });
+        for index in 2..10 {
+            image.set_pixel_color(Color::BLACK, Point { x: index, y: 2 });
+            image.set_pixel_color(Color::BLACK, Point { x: index, y: 9 });
+            image.set_pixel_color(Color::BLACK, Point { x: 9, y: index });
+            if index != 5 {
+                image.set_pixel_color(Color::BLACK, Point { x: 2, y: index });
+            }
+        }
+        let fill_color = Color::from_rgb_u32(0x00ffff);
+        let start = Point { x: 5, y: 5 };
+
+        // A regular fill leaks through the gap.
+        let mut leaked_image = image.clone();
+        flood_fill(&mut leaked_image, start, &fill_color).unwrap();
+        assert_eq!(
+            leaked_image.pixel_color(Point::zero()),
+            Some(fill_color.clone())
+        );
+
+        let result = flood_fill_with_gap_closing(&mut image, start, &fill_color, 1).unwrap();
+        assert_eq!(image.pixel_color(Point::zero()), Some(Color::CLEAR));
+        assert_eq!(image.pixel_color(Point { x: 2, y: 5 }), Some(Color::CLEAR));
+        assert_eq!(image.pixel_color(Point { x: 2, y: 2 }), Some(Color::BLACK));
+        for y in 3..9 {
+            for x in 3..9 {
+                assert_eq!(image.pixel_color(Point { x, y }), Some(fill_color.clone()));
+            }
+        }
+        assert_eq!(result, Rect::new(3, 3, 6, 6));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_flood_fill_parallel() {
+        let size = Size {
+            width: 1536,
+            height: 1024,
+        };
+        let mut image = Image::color(&Color::WHITE, size);
+        // Draw a set of walls with openings so that the filled region
+        // snakes back and forth across many bands.
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let is_wall = (x % 96 == 48 && (y + x / 96 * 300) % 1024 > 40)
+                    || (y % 80 == 40 && (x * 7 + y) % 53 < 3)
+                    || (x * 31 + y * 17) % 97 == 0;
+                if is_wall {
+                    image.set_pixel_color(Color::BLACK, Point { x, y });
+                }
+            }
+        }
+        let fill_color = Color::from_rgb_u32(0x00ffff);
+        let start = Point { x: 10, y: 500 };
+
+        let mut expected_image = image.clone();
+        let expected_region = flood_fill(&mut expected_image, start, &fill_color).unwrap();
+        let region = flood_fill_parallel(&mut image, start, &fill_color).unwrap();
+
+        assert!(image == expected_image);
+        assert_eq!(region, expected_region);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_flood_fill_parallel_with_truncated_padded_image() {
+        let size = Size {
+            width: 3,
+            height: 2,
+        };
+        let bytes_per_row = 13;
+        let data = vec![0; bytes_per_row as usize + 8];
+        let mut image = Image::new(data, size, bytes_per_row);
+        let fill_color = Color::from_rgb_u32(0x00ffff);
+
+        for start in [Point { x: 2, y: 1 }, Point { x: 0, y: 0 }] {
+            assert!(flood_fill_parallel(&mut image, start, &fill_color).is_err());
+        }
+        assert!(image.data.iter().all(|byte| *byte == 0));
+
+        let mut image = Image::new(vec![0; 24], size, 8);
+        assert!(flood_fill_parallel(&mut image, Point::zero(), &fill_color).is_err());
+    }
+
+    #[test]
+    fn test_flood_fill_with_truncated_padded_image() {
+        // Each row is padded to an odd length, and the data stops short of
+        // the last pixel, so reading it returns the same clear colour as the
+        // rest of the image but writing to it would be out of range.
+        let size = Size {
+            width: 3,
+            height: 2,
+        };
+        let bytes_per_row = 13;
+        let data = vec![0; bytes_per_row as usize + 8];
+        let mut image = Image::new(data, size, bytes_per_row);
+        let fill_color = Color::from_rgb_u32(0x00ffff);
+
+        let start = Point { x: 2, y: 1 };
+        assert!(flood_fill(&mut image, start, &fill_color).is_err());
+        let start = Point { x: 0, y: 0 };
+        assert!(flood_fill(&mut image, start, &fill_color).is_err());
+        // Nothing should have been filled before the error.
+        assert!(image.data.iter().all(|byte| *byte == 0));
+
+        let mut image = Image::new(vec![0; 24], size, 8);
+        assert!(flood_fill(&mut image, start, &fill_color).is_err());
+
+        // The same applies when only the recoloured image is truncated.
+        let reference_image = Image::new(vec![0; bytes_per_row as usize * 2], size, bytes_per_row);
+        let data = vec![0; bytes_per_row as usize + 8];
+        let mut target_image = Image::new(data, size, bytes_per_row);
+        assert!(
+            flood_fill_with_reference(&mut target_image, &reference_image, start, &fill_color)
+                .is_err()
+        );
+        assert!(target_image.data.iter().all(|byte| *byte == 0));
+    }
 }
@@ -1,5 +1,6 @@
 mod rgb_color;
 mod rgba_color;
+mod transfer;
 
 pub use rgb_color::RgbColor;
 pub use rgba_color::RgbaColor;
@@ -117,6 +118,13 @@ pub fn addition(color: &mut RgbColor, blend: &RgbColor) {
     color.clamp();
 }
 
+/// Calculate the addition blend mode, scaling the result down
+/// to keep its hue when it exceeds the maximum value.
+pub fn addition_preserving_hue(color: &mut RgbColor, blend: &RgbColor) {
+    color.add(blend);
+    color.clamp_preserving_hue();
+}
+
 // COLOUR
 
 /// Calculate color.
@@ -182,6 +182,21 @@ impl RgbColor {
         self.blue = self.blue.clamp(0.0, 1.0);
     }
 
+    /// Clamps the colour values to the acceptable range. If any channel
+    /// exceeds the maximum, all the channels are scaled down by the same
+    /// amount, which keeps the ratio between them and so the hue.
+    pub fn clamp_preserving_hue(&mut self) {
+        self.red = self.red.max(0.0);
+        self.green = self.green.max(0.0);
+        self.blue = self.blue.max(0.0);
+        let max = self.red.max(self.green).max(self.blue);
+        if max > 1.0 {
+            self.red /= max;
+            self.green /= max;
+            self.blue /= max;
+        }
+    }
+
     /// Returns the absolute value of all the channels.
     pub fn abs(&mut self) {
         self.red = self.red.abs();
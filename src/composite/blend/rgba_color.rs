@@ -1,5 +1,6 @@
 use std::ops::{Add, Mul};
 
+use super::transfer::{linear_to_srgb, srgb_to_linear};
 use super::RgbColor;
 
 /// Defines the colour type used in blend functions.
@@ -62,6 +63,28 @@ impl RgbaColor {
         }
     }
 
+    /// Creates a new blend colour from the crate colour, converting
+    /// the colour channels from sRGB to linear light.
+    pub fn from_linearized(color: &crate::Color) -> Self {
+        Self {
+            red: srgb_to_linear(color.red),
+            green: srgb_to_linear(color.green),
+            blue: srgb_to_linear(color.blue),
+            alpha: color.alpha as f32 / u8::MAX as f32,
+        }
+    }
+
+    /// Returns this colour type as a crate colour, converting
+    /// the colour channels from linear light to sRGB.
+    pub fn to_delinearized_color(&self) -> crate::Color {
+        crate::Color {
+            red: linear_to_srgb(self.red),
+            green: linear_to_srgb(self.green),
+            blue: linear_to_srgb(self.blue),
+            alpha: (self.alpha * u8::MAX as f32).round() as u8,
+        }
+    }
+
     /// Returns this colour type as a crate colour.
     pub fn to_color(&self) -> crate::Color {
         let max = u8::MAX as f32;
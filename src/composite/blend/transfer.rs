@@ -0,0 +1,45 @@
+use std::sync::OnceLock;
+
+/// The number of entries in the table that converts linear values
+/// back to sRGB.
+const LINEAR_TABLE_SIZE: usize = 4096;
+
+/// Returns the linear light value of an 8 bit sRGB value,
+/// in the range 0 to 1.
+pub fn srgb_to_linear(value: u8) -> f32 {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0.0; 256];
+        for (value, entry) in table.iter_mut().enumerate() {
+            let value = value as f32 / u8::MAX as f32;
+            *entry = if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        table
+    });
+    table[value as usize]
+}
+
+/// Returns the 8 bit sRGB value of a linear light value
+/// in the range 0 to 1.
+pub fn linear_to_srgb(value: f32) -> u8 {
+    static TABLE: OnceLock<[u8; LINEAR_TABLE_SIZE]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0; LINEAR_TABLE_SIZE];
+        for (index, entry) in table.iter_mut().enumerate() {
+            let value = index as f32 / (LINEAR_TABLE_SIZE - 1) as f32;
+            let value = if value <= 0.0031308 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1.0 / 2.4) - 0.055
+            };
+            *entry = (value * u8::MAX as f32).round() as u8;
+        }
+        table
+    });
+    let index = (value.clamp(0.0, 1.0) * (LINEAR_TABLE_SIZE - 1) as f32).round() as usize;
+    table[index]
+}
@@ -1,25 +1,317 @@
 use std::cmp::min;
 
-use crate::{BlendMode, Color, Image};
+use crate::{BlendMode, Color, Image, Point, Rect};
 
 use super::blend::{self, RgbaColor};
-use super::operation::Operation;
-use super::{Either, Layer};
+use super::operation::{Operation, OperationItem};
+use super::{Either, Group, Layer};
 
 /// Composites multiple images together and returns the result.
 pub fn composite(operation: &Operation) -> Image {
     let mut output = Image::empty(operation.size);
 
-    for layer in operation.layers.iter() {
-        draw_layer_over_image(&mut output, &layer);
+    let options = BlendOptions {
+        linear_blending: operation.linear_blending,
+        preserve_hue_on_clamp: operation.preserve_hue_on_clamp,
+    };
+    let items = &operation.items;
+    let mut index = 0;
+    while index < items.len() {
+        // Consecutive normal layers are blended in a single pass so that
+        // each output pixel is only read and written once.
+        let run: Vec<&Layer> = items[index..]
+            .iter()
+            .map_while(|item| match item {
+                OperationItem::Layer(layer)
+                    if layer.blend_mode == BlendMode::Normal && !layer.tiled =>
+                {
+                    Some(layer)
+                }
+                _ => None,
+            })
+            .collect();
+        if run.len() > 1 {
+            draw_normal_layers_over_image(&mut output, &run, options);
+            index += run.len();
+            continue;
+        }
+        match &items[index] {
+            OperationItem::Layer(layer) => draw_layer(&mut output, layer, options),
+            OperationItem::Group(group) => draw_group(&mut output, group, options),
+        }
+        index += 1;
     }
 
     output
 }
 
+/// Composites only the `tile` region of the operation’s canvas and returns
+/// it as a `tile` sized image. The layers keep their full canvas positions,
+/// so the result is identical to cropping the full composite to the tile,
+/// which allows very large canvases to be rendered tile by tile.
+pub fn composite_tile(operation: &Operation, tile: Rect<i32>) -> Image {
+    let items = operation
+        .items
+        .iter()
+        .map(|item| match item {
+            OperationItem::Layer(layer) => OperationItem::Layer(tile_layer(layer, tile, operation)),
+            OperationItem::Group(group) => OperationItem::Group(Group {
+                layers: group
+                    .layers
+                    .iter()
+                    .map(|layer| tile_layer(layer, tile, operation))
+                    .collect(),
+                blend_mode: group.blend_mode,
+                opacity: group.opacity,
+                isolated: group.isolated,
+            }),
+        })
+        .collect();
+
+    let mut tile_operation = Operation::with_items(items, tile.size.into());
+    tile_operation.should_premultiply = operation.should_premultiply;
+    tile_operation.linear_blending = operation.linear_blending;
+    tile_operation.preserve_hue_on_clamp = operation.preserve_hue_on_clamp;
+    composite(&tile_operation)
+}
+
+/// Returns a copy of a layer that is positioned and clipped for drawing
+/// the `tile` region of the operation’s canvas.
+fn tile_layer<'a>(layer: &'a Layer, tile: Rect<i32>, operation: &Operation) -> Layer<'a> {
+    // Nothing is drawn outside of the canvas, even where the tile extends
+    // past it.
+    let canvas_bounds = Rect {
+        origin: Point::zero() - tile.origin,
+        size: operation.size.into(),
+    };
+    let mut tile_layer = Layer::new(layer.image.as_ref(), layer.position);
+    tile_layer.size_on_canvas = layer.size_on_canvas;
+    tile_layer.blend_mode = layer.blend_mode;
+    tile_layer.opacity = layer.opacity;
+    tile_layer.clip_rect = Some(
+        layer
+            .clip_rect
+            .map(|clip_rect| Rect {
+                origin: clip_rect.origin - tile.origin,
+                size: clip_rect.size,
+            })
+            .map_or(Some(canvas_bounds), |clip_rect| {
+                clip_rect.intersection(&canvas_bounds)
+            })
+            .unwrap_or(Rect::new(0, 0, 0, 0)),
+    );
+    tile_layer.tiled = layer.tiled;
+    // Offsetting the rounded position keeps the layer on the same
+    // pixels, however far the tile is from the origin.
+    let position = layer.rounded_position() - tile.origin;
+    tile_layer.set_position_f64(Point {
+        x: position.x as f64,
+        y: position.y as f64,
+    });
+    tile_layer
+}
+
+/// Draws a run of layers over an image in a single pass, blending each
+/// pixel through all of the layers before writing it back.
+/// The output is identical to drawing each layer in turn.
+fn draw_normal_layers_over_image(image: &mut Image, layers: &[&Layer], options: BlendOptions) {
+    let image_bounds = Rect {
+        origin: Point::zero(),
+        size: image.size.into(),
+    };
+    let placements: Vec<(&Image, Rect<i32>)> = layers
+        .iter()
+        .map(|layer| (layer.image.as_ref(), layer.canvas_bounds()))
+        .collect();
+
+    let Some(region) = placements
+        .iter()
+        .map(|(_, bounds)| *bounds)
+        .reduce(|a, b| a.union(&b))
+        .and_then(|region| region.intersection(&image_bounds))
+    else {
+        return;
+    };
+
+    for y in region.min_y()..region.max_y() {
+        let target_row = y as usize * image.bytes_per_row as usize;
+        for x in region.min_x()..region.max_x() {
+            let target_offset = target_row + x as usize * 4;
+            let base_color: [u8; 4] = image.data[target_offset..target_offset + 4]
+                .try_into()
+                .unwrap();
+            let mut base_color: Color = base_color.into();
+
+            for ((layer_image, bounds), layer) in placements.iter().zip(layers) {
+                let layer_x = x - bounds.origin.x;
+                let layer_y = y - bounds.origin.y;
+                if layer_x < 0
+                    || layer_y < 0
+                    || layer_x >= bounds.size.width
+                    || layer_y >= bounds.size.height
+                    || !is_within_clip_rect(layer, Point { x, y })
+                {
+                    continue;
+                }
+                let offset =
+                    layer_y as usize * layer_image.bytes_per_row as usize + layer_x as usize * 4;
+                let blend_color: [u8; 4] = layer_image.data[offset..offset + 4].try_into().unwrap();
+                let blend_color: Color = blend_color.into();
+                blend_colors(
+                    &mut base_color,
+                    &blend_color,
+                    layer.blend_mode,
+                    layer.opacity,
+                    options,
+                );
+            }
+
+            let base_color: [u8; 4] = base_color.into();
+            image.data[target_offset..target_offset + 4].copy_from_slice(&base_color);
+        }
+    }
+}
+
+/// Draws a group of layers over an image.
+///
+/// An isolated group composites its layers against a transparent backdrop,
+/// then draws the result over the image using the group’s blend mode and
+/// opacity, as described by the group isolation section of the W3C
+/// compositing spec.
+///
+/// The layers of a non-isolated group are blended directly with the image,
+/// much like a pass through group, with the group’s opacity fading between
+/// the original image and the result. When the group has a blend mode
+/// other than normal, the backdrop is removed from the result to recover
+/// the group’s own colour, which is then blended with the image using the
+/// group’s blend mode and opacity, as in the PDF transparency model.
+pub fn draw_group_over_image(image: &mut Image, group: &Group) {
+    draw_group(image, group, BlendOptions::default());
+}
+
+/// Draws a group of layers over an image with the given blending options.
+fn draw_group(image: &mut Image, group: &Group, options: BlendOptions) {
+    if group.isolated {
+        let mut group_image = Image::empty(image.size);
+        for layer in group.layers.iter() {
+            draw_layer(&mut group_image, layer, options);
+        }
+        let mut group_layer = Layer::new(&group_image, Point::zero());
+        group_layer.blend_mode = group.blend_mode;
+        group_layer.opacity = group.opacity;
+        draw_layer(image, &group_layer, options);
+        return;
+    }
+
+    let backdrop = image.clone();
+    for layer in group.layers.iter() {
+        draw_layer(image, layer, options);
+    }
+
+    if group.blend_mode != BlendMode::Normal {
+        blend_non_isolated_group(image, &backdrop, group, options);
+        return;
+    }
+
+    if group.opacity >= 1.0 {
+        return;
+    }
+
+    let opacity = group.opacity.max(0.0);
+    for (pixel, backdrop_pixel) in image
+        .data
+        .chunks_exact_mut(4)
+        .zip(backdrop.data.chunks_exact(4))
+    {
+        let result: [u8; 4] = (&*pixel).try_into().unwrap();
+        let backdrop_color: [u8; 4] = backdrop_pixel.try_into().unwrap();
+        let result = premultiplied(&result.into());
+        let backdrop_color = premultiplied(&backdrop_color.into());
+        let mut output = result * opacity + backdrop_color * (1.0 - opacity);
+        output.unpremultiply();
+        let output: [u8; 4] = output.to_color().into();
+        pixel.copy_from_slice(&output);
+    }
+}
+
+/// Replaces the result of a non-isolated group, which has already been
+/// drawn over the backdrop, with the group’s own colour blended over the
+/// backdrop using the group’s blend mode and opacity.
+fn blend_non_isolated_group(
+    image: &mut Image,
+    backdrop: &Image,
+    group: &Group,
+    options: BlendOptions,
+) {
+    // The group’s alpha is the union of its layers’ alphas, which is the
+    // same as compositing them against a transparent backdrop.
+    let mut group_image = Image::new(vec![0; image.data.len()], image.size, image.bytes_per_row);
+    for layer in group.layers.iter() {
+        draw_layer(&mut group_image, layer, options);
+    }
+
+    for ((pixel, backdrop_pixel), group_pixel) in image
+        .data
+        .chunks_exact_mut(4)
+        .zip(backdrop.data.chunks_exact(4))
+        .zip(group_image.data.chunks_exact(4))
+    {
+        let backdrop_color: [u8; 4] = backdrop_pixel.try_into().unwrap();
+        let mut output: Color = backdrop_color.into();
+        let group_alpha = group_pixel[3] as f32 / 255.0;
+        if group_alpha > 0.0 {
+            // Backdrop removal, from the PDF specification:
+            // C = Cn + (Cn - C0) × (α0 / αg - α0)
+            let backdrop_alpha = backdrop_pixel[3] as f32 / 255.0;
+            let factor = backdrop_alpha / group_alpha - backdrop_alpha;
+            let channel = |index: usize| {
+                let result = pixel[index] as f32;
+                let backdrop = backdrop_pixel[index] as f32;
+                (result + (result - backdrop) * factor)
+                    .round()
+                    .clamp(0.0, 255.0) as u8
+            };
+            let group_color = Color {
+                red: channel(0),
+                green: channel(1),
+                blue: channel(2),
+                alpha: group_pixel[3],
+            };
+            blend_colors(
+                &mut output,
+                &group_color,
+                group.blend_mode,
+                group.opacity,
+                options,
+            );
+        }
+        let output: [u8; 4] = output.into();
+        pixel.copy_from_slice(&output);
+    }
+}
+
+/// Returns the colour with its colour channels premultiplied by its alpha.
+fn premultiplied(color: &Color) -> RgbaColor {
+    let mut color = RgbaColor::from(color);
+    color.red *= color.alpha;
+    color.green *= color.alpha;
+    color.blue *= color.alpha;
+    color
+}
+
 /// Draws a layer over an image.
 pub fn draw_layer_over_image(image: &mut Image, layer: &Layer) {
-    let location = layer.position.rounded();
+    draw_layer(image, layer, BlendOptions::default());
+}
+
+/// Draws a layer over an image with the given blending options.
+fn draw_layer(image: &mut Image, layer: &Layer, options: BlendOptions) {
+    if layer.tiled {
+        draw_tiled_layer(image, layer, options);
+        return;
+    }
+
+    let location = layer.rounded_position();
     let start_x = if location.x < 0 { 0 } else { location.x as u32 };
     if start_x >= image.size.width {
         return;
@@ -76,6 +368,14 @@ pub fn draw_layer_over_image(image: &mut Image, layer: &Layer) {
         let target_offset = (target_offset + (start_x as i32) * 4) as usize;
         // Using a second loop was a tiny bit faster than splicing the vec.
         for x in (0..required_width * 4).step_by(4) {
+            let canvas_location = Point {
+                x: start_x as i32 + (x / 4) as i32,
+                y: (target_y_offset + y) as i32,
+            };
+            if !is_within_clip_rect(layer, canvas_location) {
+                continue;
+            }
+
             let start = offset + x + x_offset;
             let blend_color: [u8; 4] = match &layer.image {
                 Either::Owned(image) => {
@@ -101,6 +401,7 @@ pub fn draw_layer_over_image(image: &mut Image, layer: &Layer) {
                 &blend_color,
                 layer.blend_mode,
                 layer.opacity,
+                options,
             );
             // let base_color = Color::RED;
 
@@ -112,18 +413,276 @@ pub fn draw_layer_over_image(image: &mut Image, layer: &Layer) {
     }
 }
 
+/// Draws a layer over the whole of an image, repeating the layer’s image
+/// in both directions starting from its position.
+fn draw_tiled_layer(image: &mut Image, layer: &Layer, options: BlendOptions) {
+    let layer_image = layer.image.as_ref();
+    if layer_image.size.width == 0 || layer_image.size.height == 0 {
+        return;
+    }
+    let layer_width = layer_image.size.width as i32;
+    let layer_height = layer_image.size.height as i32;
+    let phase = layer.rounded_position();
+
+    for y in 0..image.size.height as i32 {
+        let layer_y = (y - phase.y).rem_euclid(layer_height) as usize;
+        let layer_row = layer_y * layer_image.bytes_per_row as usize;
+        let target_row = y as usize * image.bytes_per_row as usize;
+        for x in 0..image.size.width as i32 {
+            if !is_within_clip_rect(layer, Point { x, y }) {
+                continue;
+            }
+            let layer_x = (x - phase.x).rem_euclid(layer_width) as usize;
+            let offset = layer_row + layer_x * 4;
+            let blend_color: [u8; 4] = layer_image.data[offset..offset + 4].try_into().unwrap();
+            let blend_color: Color = blend_color.into();
+
+            let target_offset = target_row + x as usize * 4;
+            let base_color: [u8; 4] = image.data[target_offset..target_offset + 4]
+                .try_into()
+                .unwrap();
+            let mut base_color: Color = base_color.into();
+            blend_colors(
+                &mut base_color,
+                &blend_color,
+                layer.blend_mode,
+                layer.opacity,
+                options,
+            );
+
+            let base_color: [u8; 4] = base_color.into();
+            image.data[target_offset..target_offset + 4].copy_from_slice(&base_color);
+        }
+    }
+}
+
+/// Draws a layer over an image, limited to a selection on the canvas.
+/// The layer’s contribution fades out over `feather` pixels inside the
+/// edges of the selection, and nothing is drawn outside of it.
+pub fn draw_layer_over_image_feathered(
+    image: &mut Image,
+    layer: &Layer,
+    selection: Rect<i32>,
+    feather: f32,
+) {
+    let layer_image = layer.image.as_ref();
+    let layer_bounds = layer.canvas_bounds();
+    let image_bounds = Rect {
+        origin: Point::zero(),
+        size: image.size.into(),
+    };
+    let Some(region) = layer_bounds
+        .intersection(&image_bounds)
+        .and_then(|region| region.intersection(&selection))
+    else {
+        return;
+    };
+
+    for y in region.min_y()..region.max_y() {
+        for x in region.min_x()..region.max_x() {
+            if !is_within_clip_rect(layer, Point { x, y }) {
+                continue;
+            }
+            // Measure from the pixel’s centre to the nearest edge.
+            let distance_to_edge = [
+                x - selection.min_x(),
+                selection.max_x() - 1 - x,
+                y - selection.min_y(),
+                selection.max_y() - 1 - y,
+            ]
+            .into_iter()
+            .min()
+            .unwrap_or(0) as f32
+                + 0.5;
+            let coverage = if feather > 0.0 {
+                (distance_to_edge / feather).min(1.0)
+            } else {
+                1.0
+            };
+
+            let offset = (y - layer_bounds.origin.y) as usize * layer_image.bytes_per_row as usize
+                + (x - layer_bounds.origin.x) as usize * 4;
+            let blend_color: [u8; 4] = layer_image.data[offset..offset + 4].try_into().unwrap();
+            let target_offset = y as usize * image.bytes_per_row as usize + x as usize * 4;
+            let base_color: [u8; 4] = image.data[target_offset..target_offset + 4]
+                .try_into()
+                .unwrap();
+            let mut base_color: Color = base_color.into();
+            blend_colors(
+                &mut base_color,
+                &blend_color.into(),
+                layer.blend_mode,
+                layer.opacity * coverage,
+                BlendOptions::default(),
+            );
+            let base_color: [u8; 4] = base_color.into();
+            image.data[target_offset..target_offset + 4].copy_from_slice(&base_color);
+        }
+    }
+}
+
+/// Draws a layer over an image, but only keeps the result of the blend in
+/// the channels enabled in `channel_mask`, in RGBA order. The other
+/// channels keep the image’s original values, so blending only the red
+/// channel of an offset layer gives a chromatic aberration effect.
+pub fn draw_layer_over_image_masked_channels(
+    image: &mut Image,
+    layer: &Layer,
+    channel_mask: [bool; 4],
+) {
+    let layer_image = layer.image.as_ref();
+    let layer_bounds = layer.canvas_bounds();
+    let image_bounds = Rect {
+        origin: Point::zero(),
+        size: image.size.into(),
+    };
+    let Some(region) = layer_bounds.intersection(&image_bounds) else {
+        return;
+    };
+
+    for y in region.min_y()..region.max_y() {
+        for x in region.min_x()..region.max_x() {
+            if !is_within_clip_rect(layer, Point { x, y }) {
+                continue;
+            }
+            let offset = (y - layer_bounds.origin.y) as usize * layer_image.bytes_per_row as usize
+                + (x - layer_bounds.origin.x) as usize * 4;
+            let blend_color: [u8; 4] = layer_image.data[offset..offset + 4].try_into().unwrap();
+            let target_offset = y as usize * image.bytes_per_row as usize + x as usize * 4;
+            let base_color: [u8; 4] = image.data[target_offset..target_offset + 4]
+                .try_into()
+                .unwrap();
+            let mut blended: Color = base_color.into();
+            blend_colors(
+                &mut blended,
+                &blend_color.into(),
+                layer.blend_mode,
+                layer.opacity,
+                BlendOptions::default(),
+            );
+            let blended: [u8; 4] = blended.into();
+            for channel in 0..4 {
+                if channel_mask[channel] {
+                    image.data[target_offset + channel] = blended[channel];
+                }
+            }
+        }
+    }
+}
+
+/// Draws a layer over an image, wrapping any part of the layer that
+/// extends past an edge of the image around to the opposite edge instead
+/// of clipping it, so that tileable textures stay tileable while editing.
+/// The clip rect is applied to the wrapped canvas locations.
+pub fn draw_layer_wrapped(image: &mut Image, layer: &Layer) {
+    if image.size.width == 0 || image.size.height == 0 {
+        return;
+    }
+    let layer_image = layer.image.as_ref();
+    let location = layer.rounded_position();
+    let image_width = image.size.width as i32;
+    let image_height = image.size.height as i32;
+
+    for layer_y in 0..layer_image.size.height as usize {
+        let y = (location.y + layer_y as i32).rem_euclid(image_height);
+        let layer_row = layer_y * layer_image.bytes_per_row as usize;
+        let target_row = y as usize * image.bytes_per_row as usize;
+        for layer_x in 0..layer_image.size.width as usize {
+            let x = (location.x + layer_x as i32).rem_euclid(image_width);
+            if !is_within_clip_rect(layer, Point { x, y }) {
+                continue;
+            }
+            let offset = layer_row + layer_x * 4;
+            let blend_color: [u8; 4] = layer_image.data[offset..offset + 4].try_into().unwrap();
+
+            let target_offset = target_row + x as usize * 4;
+            let base_color: [u8; 4] = image.data[target_offset..target_offset + 4]
+                .try_into()
+                .unwrap();
+            let mut base_color: Color = base_color.into();
+            blend_colors(
+                &mut base_color,
+                &blend_color.into(),
+                layer.blend_mode,
+                layer.opacity,
+                BlendOptions::default(),
+            );
+            let base_color: [u8; 4] = base_color.into();
+            image.data[target_offset..target_offset + 4].copy_from_slice(&base_color);
+        }
+    }
+}
+
+/// Blends a colour over another in place, as the compositor would when
+/// drawing a pixel of a layer with the given blend mode and opacity.
+pub(crate) fn blend_color_over(
+    color: &mut Color,
+    blend_color: &Color,
+    blend_mode: BlendMode,
+    opacity: f32,
+) {
+    blend_colors(
+        color,
+        blend_color,
+        blend_mode,
+        opacity,
+        BlendOptions::default(),
+    );
+}
+
+/// Returns whether a canvas location is inside the layer’s clip rect.
+/// Layers without a clip rect cover the whole canvas.
+fn is_within_clip_rect(layer: &Layer, location: Point<i32>) -> bool {
+    layer.clip_rect.is_none_or(|clip_rect| {
+        location.x >= clip_rect.min_x()
+            && location.x < clip_rect.max_x()
+            && location.y >= clip_rect.min_y()
+            && location.y < clip_rect.max_y()
+    })
+}
+
+/// Options that change how colours are blended.
+#[derive(Debug, Clone, Copy, Default)]
+struct BlendOptions {
+    /// Whether the colours are converted to linear light before
+    /// blending, with the result converted back to sRGB.
+    linear_blending: bool,
+    /// Whether blend results that exceed the maximum value are
+    /// scaled down to keep the ratio between the channels, rather
+    /// than clipping each channel independently.
+    preserve_hue_on_clamp: bool,
+}
+
 /// Blends one colour with another.
-fn blend_colors(color: &mut Color, blend_color: &Color, blend_mode: BlendMode, opacity: f32) {
+fn blend_colors(
+    color: &mut Color,
+    blend_color: &Color,
+    blend_mode: BlendMode,
+    opacity: f32,
+    options: BlendOptions,
+) {
     if color.alpha == 0 && blend_color.alpha == 0 {
         return;
     };
 
-    let mut base_rgba = blend::RgbaColor::from(color);
-    let mut blend_rgba = blend::RgbaColor::from(blend_color);
+    let (mut base_rgba, mut blend_rgba) = if options.linear_blending {
+        (
+            blend::RgbaColor::from_linearized(color),
+            blend::RgbaColor::from_linearized(blend_color),
+        )
+    } else {
+        (
+            blend::RgbaColor::from(color),
+            blend::RgbaColor::from(blend_color),
+        )
+    };
     let mut base_rgb = blend::RgbColor::from_rgba_color(&base_rgba);
     let blend_rgb = blend::RgbColor::from_rgba_color(&blend_rgba);
 
     match blend_mode {
+        BlendMode::Addition if options.preserve_hue_on_clamp => {
+            blend::addition_preserving_hue(&mut base_rgb, &blend_rgb)
+        }
         BlendMode::Addition => blend::addition(&mut base_rgb, &blend_rgb),
         BlendMode::Color => blend::color(&mut base_rgb, &blend_rgb),
         BlendMode::ColorBurn => blend::color_burn(&mut base_rgb, &blend_rgb),
@@ -182,7 +741,11 @@ fn blend_colors(color: &mut Color, blend_color: &Color, blend_mode: BlendMode, o
         output.unpremultiply();
     }
 
-    let result = output.to_color();
+    let result = if options.linear_blending {
+        output.to_delinearized_color()
+    } else {
+        output.to_color()
+    };
 
     color.red = result.red;
     color.green = result.green;
@@ -201,7 +764,13 @@ mod test {
         let mut blend_color = Color::from_rgb_u32(0x0000ff);
         blend_color.alpha = 128;
 
-        blend_colors(&mut color, &blend_color, BlendMode::Normal, 1.0);
+        blend_colors(
+            &mut color,
+            &blend_color,
+            BlendMode::Normal,
+            1.0,
+            BlendOptions::default(),
+        );
 
         assert_eq!(color.red, 0x7f, "Reds don’t match.");
         assert_eq!(color.green, 0x7f, "Greens don’t match.");
@@ -217,7 +786,13 @@ mod test {
         let mut blend_color = Color::from_rgb_u32(0x0000ff);
         blend_color.alpha = 128;
 
-        blend_colors(&mut color, &blend_color, BlendMode::Normal, 1.0);
+        blend_colors(
+            &mut color,
+            &blend_color,
+            BlendMode::Normal,
+            1.0,
+            BlendOptions::default(),
+        );
 
         assert_eq!(color.red, 0x2a, "Reds don’t match.");
         assert_eq!(color.green, 0x2a, "Greens don’t match.");
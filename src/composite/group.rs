@@ -0,0 +1,34 @@
+use crate::BlendMode;
+
+use super::layer::Layer;
+
+/// Represents a group of layers that are composited together
+/// and then blended with whatever is beneath the group.
+#[derive(Debug, Clone)]
+pub struct Group<'a> {
+    /// The layers in the group, from bottom to top.
+    pub layers: Vec<Layer<'a>>,
+    /// The group’s blend mode.
+    pub blend_mode: BlendMode,
+    /// The group’s opacity.
+    pub opacity: f32,
+    /// Whether or not the group is isolated. An isolated group composites
+    /// its layers against a transparent backdrop before blending the result,
+    /// whereas the layers in a non-isolated group blend directly with the
+    /// content beneath the group.
+    pub isolated: bool,
+}
+
+// MARK: Creation
+
+impl<'a> Group<'a> {
+    /// Creates a new non-isolated group.
+    pub fn new(layers: Vec<Layer<'a>>) -> Self {
+        Self {
+            layers,
+            blend_mode: BlendMode::default(),
+            opacity: 1.0,
+            isolated: false,
+        }
+    }
+}
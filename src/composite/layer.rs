@@ -1,4 +1,4 @@
-use crate::{BlendMode, Image, Point, Size};
+use crate::{BlendMode, Image, Point, Rect, Size};
 
 /// Represents a layer that can be composited with
 /// other layers to create a single image.
@@ -8,12 +8,22 @@ pub struct Layer<'a> {
     pub image: Either<'a, Image>,
     /// The position of the image on the canvas.
     pub position: Point<f32>,
+    /// A double precision position of the image on the canvas, set with
+    /// `set_position_f64`. It takes precedence over `position` until
+    /// `position` is assigned a different value.
+    position_f64: Option<Point<f64>>,
     /// The size of the image on the canvas.
     pub size_on_canvas: Size<f32>,
     /// The layer’s blend mode.
     pub blend_mode: BlendMode,
     /// The layer’s opacity.
     pub opacity: f32,
+    /// The region of the canvas the layer is limited to, if any.
+    /// Pixels outside of this rectangle are left untouched.
+    pub clip_rect: Option<Rect<i32>>,
+    /// Whether the layer’s image repeats to cover the whole canvas.
+    /// The position sets the phase of the repetition.
+    pub tiled: bool,
 }
 
 /// Defines a property that can be either owned or borrowed.
@@ -25,6 +35,15 @@ pub enum Either<'a, T> {
     Borrowed(&'a T),
 }
 
+impl<T> AsRef<T> for Either<'_, T> {
+    fn as_ref(&self) -> &T {
+        match self {
+            Either::Owned(value) => value,
+            Either::Borrowed(value) => value,
+        }
+    }
+}
+
 // MARK: Creation
 
 impl<'a> Layer<'a> {
@@ -34,9 +53,12 @@ impl<'a> Layer<'a> {
         Self {
             image: Either::Borrowed(image),
             position,
+            position_f64: None,
             size_on_canvas,
             blend_mode: BlendMode::default(),
             opacity: 1.0,
+            clip_rect: None,
+            tiled: false,
         }
     }
 
@@ -46,9 +68,66 @@ impl<'a> Layer<'a> {
         Self {
             image: Either::Owned(image),
             position,
+            position_f64: None,
             size_on_canvas,
             blend_mode: BlendMode::default(),
             opacity: 1.0,
+            clip_rect: None,
+            tiled: false,
+        }
+    }
+}
+
+// MARK: Position
+
+impl Layer<'_> {
+    /// Sets the position of the image on the canvas with double precision.
+    /// Use this for very large canvases, where `f32` positions lose
+    /// precision. `position` is updated to the nearest `f32` value.
+    pub fn set_position_f64(&mut self, position: Point<f64>) {
+        self.position = Point {
+            x: position.x as f32,
+            y: position.y as f32,
+        };
+        self.position_f64 = Some(position);
+    }
+
+    /// Returns the double precision position of the image on the canvas,
+    /// if one was set with `set_position_f64`. Assigning a different value
+    /// to `position` afterwards replaces it, in which case `None` is
+    /// returned.
+    pub fn position_f64(&self) -> Option<Point<f64>> {
+        self.position_f64.filter(|position| {
+            position.x as f32 == self.position.x && position.y as f32 == self.position.y
+        })
+    }
+
+    /// Returns the position of the image on the canvas, rounded to the
+    /// nearest pixel. The double precision position is used if it is set.
+    pub fn rounded_position(&self) -> Point<i32> {
+        match self.position_f64() {
+            Some(position) => position.rounded(),
+            None => self.position.rounded(),
+        }
+    }
+
+    /// Returns the region the layer’s image occupies on the canvas, using
+    /// the rounded position and the size of the image.
+    pub fn canvas_bounds(&self) -> Rect<i32> {
+        Rect {
+            origin: self.rounded_position(),
+            size: self.image.as_ref().size.into(),
         }
     }
 }
+
+// MARK: Hit testing
+
+impl Layer<'_> {
+    /// Returns whether a point on the canvas lands on a pixel of the
+    /// layer’s image with an alpha of at least `alpha_threshold`.
+    pub fn is_hit(&self, canvas_point: Point<i32>, alpha_threshold: u8) -> bool {
+        let point = canvas_point - self.rounded_position();
+        self.image.as_ref().is_hit(point, alpha_threshold)
+    }
+}
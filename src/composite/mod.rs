@@ -1,8 +1,10 @@
 mod blend;
 mod compositor;
+mod group;
 mod layer;
 mod operation;
 
 pub use compositor::*;
+pub use group::*;
 pub use layer::*;
 pub use operation::*;
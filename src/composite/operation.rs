@@ -1,16 +1,49 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
 use crate::Size;
 
-use super::layer::Layer;
+use super::{group::Group, layer::Layer};
+
+/// Represents an item that can be composited by an operation.
+#[derive(Debug, Clone)]
+pub enum OperationItem<'a> {
+    /// A single layer.
+    Layer(Layer<'a>),
+    /// A group of layers, composited together before being blended
+    /// with the items beneath it.
+    Group(Group<'a>),
+}
+
+impl<'a> From<Layer<'a>> for OperationItem<'a> {
+    fn from(layer: Layer<'a>) -> Self {
+        Self::Layer(layer)
+    }
+}
+
+impl<'a> From<Group<'a>> for OperationItem<'a> {
+    fn from(group: Group<'a>) -> Self {
+        Self::Group(group)
+    }
+}
 
 /// Represents an operation for the compositor.
 #[derive(Debug)]
 pub struct Operation<'a> {
-    /// The layers to composite.
-    pub layers: Vec<Layer<'a>>,
+    /// The layers and groups to composite, from bottom to top.
+    pub items: Vec<OperationItem<'a>>,
     /// The size of the canvas on which to composite the images.
     pub size: Size<u32>,
     /// Whether or not the final output should be premultiplied.
     pub should_premultiply: bool,
+    /// Whether or not the layers should be blended in linear light,
+    /// rather than in sRGB space. Blending in linear light gives
+    /// physically correct transitions, which are brighter in the midtones.
+    pub linear_blending: bool,
+    /// Whether or not blend results that exceed the maximum value
+    /// should be scaled down to keep their hue, rather than clipping
+    /// each channel independently. This applies to the addition
+    /// blend mode.
+    pub preserve_hue_on_clamp: bool,
 }
 
 // CREATION
@@ -18,10 +51,104 @@ pub struct Operation<'a> {
 impl<'a> Operation<'a> {
     /// Creates a new operation.
     pub fn new(layers: Vec<Layer<'a>>, size: Size<u32>) -> Self {
+        let items = layers.into_iter().map(OperationItem::Layer).collect();
+        Self::with_items(items, size)
+    }
+
+    /// Creates a new operation that composites a mix of layers and groups.
+    pub fn with_items(items: Vec<OperationItem<'a>>, size: Size<u32>) -> Self {
         Self {
-            layers,
+            items,
             size,
             should_premultiply: false,
+            linear_blending: false,
+            preserve_hue_on_clamp: false,
+        }
+    }
+}
+
+// MARK: Layers
+
+impl<'a> Operation<'a> {
+    /// Returns the layers that are not in a group, from bottom to top.
+    /// Layers can be added with `items.push(layer.into())`.
+    pub fn layers(&self) -> impl Iterator<Item = &Layer<'a>> {
+        self.items.iter().filter_map(|item| match item {
+            OperationItem::Layer(layer) => Some(layer),
+            OperationItem::Group(_) => None,
+        })
+    }
+
+    /// Returns mutable references to the layers that are not in a group,
+    /// from bottom to top.
+    pub fn layers_mut(&mut self) -> impl Iterator<Item = &mut Layer<'a>> {
+        self.items.iter_mut().filter_map(|item| match item {
+            OperationItem::Layer(layer) => Some(layer),
+            OperationItem::Group(_) => None,
+        })
+    }
+}
+
+// MARK: Caching
+
+impl Operation<'_> {
+    /// Returns a key for the output of the operation, for caching
+    /// composited results. The key combines the canvas size and options
+    /// with each layer’s image content, rounded position, blend mode,
+    /// opacity and other properties, along with the properties of each
+    /// group, so operations with equal keys produce the same output. Like
+    /// `Image::content_hash`, the key is only stable within a single build.
+    pub fn render_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.size.width.hash(&mut hasher);
+        self.size.height.hash(&mut hasher);
+        self.should_premultiply.hash(&mut hasher);
+        self.linear_blending.hash(&mut hasher);
+        self.preserve_hue_on_clamp.hash(&mut hasher);
+
+        self.items.len().hash(&mut hasher);
+        for item in &self.items {
+            match item {
+                OperationItem::Layer(layer) => {
+                    0u8.hash(&mut hasher);
+                    hash_layer(layer, &mut hasher);
+                }
+                OperationItem::Group(group) => {
+                    1u8.hash(&mut hasher);
+                    group.blend_mode.hash(&mut hasher);
+                    group.opacity.to_bits().hash(&mut hasher);
+                    group.isolated.hash(&mut hasher);
+                    group.layers.len().hash(&mut hasher);
+                    for layer in &group.layers {
+                        hash_layer(layer, &mut hasher);
+                    }
+                }
+            }
         }
+        hasher.finish()
     }
 }
+
+/// Feeds the properties of a layer that affect the output into a hasher.
+fn hash_layer(layer: &Layer, hasher: &mut DefaultHasher) {
+    layer.image.as_ref().content_hash().hash(hasher);
+    let position = layer.rounded_position();
+    position.x.hash(hasher);
+    position.y.hash(hasher);
+    layer.size_on_canvas.width.to_bits().hash(hasher);
+    layer.size_on_canvas.height.to_bits().hash(hasher);
+    layer.blend_mode.hash(hasher);
+    layer.opacity.to_bits().hash(hasher);
+    layer
+        .clip_rect
+        .map(|rect| {
+            (
+                rect.origin.x,
+                rect.origin.y,
+                rect.size.width,
+                rect.size.height,
+            )
+        })
+        .hash(hasher);
+    layer.tiled.hash(hasher);
+}
@@ -1,3 +1,5 @@
+use std::ops::Add;
+
 use num_traits::{Float, Num, Zero};
 
 /// A set of offsets in each of the four cardinal directions.
@@ -51,9 +53,63 @@ impl<T: Num + Copy + Zero> EdgeInsets<T> {
 impl<T: Float> EdgeInsets<T> {
     /// Rounds all of the insets to integer values.
     pub fn round(&mut self) {
-        self.top.round();
-        self.bottom.round();
-        self.left.round();
-        self.right.round();
+        self.top = self.top.round();
+        self.bottom = self.bottom.round();
+        self.left = self.left.round();
+        self.right = self.right.round();
+    }
+}
+
+// MARK: Sums
+
+impl<T: Num + Copy> EdgeInsets<T> {
+    /// Returns the total of the left and right insets.
+    pub fn horizontal(&self) -> T {
+        self.left + self.right
+    }
+
+    /// Returns the total of the top and bottom insets.
+    pub fn vertical(&self) -> T {
+        self.top + self.bottom
+    }
+}
+
+// MARK: Maths
+
+impl<T: Num + Copy> Add for EdgeInsets<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            top: self.top + rhs.top,
+            left: self.left + rhs.left,
+            bottom: self.bottom + rhs.bottom,
+            right: self.right + rhs.right,
+        }
+    }
+}
+
+// MARK: Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round() {
+        let mut insets = EdgeInsets::new(1.4, 2.6, 3.5, 4.1);
+        insets.round();
+        assert_eq!(insets.top, 1.0);
+        assert_eq!(insets.left, 3.0);
+        assert_eq!(insets.bottom, 4.0);
+        assert_eq!(insets.right, 4.0);
+    }
+
+    #[test]
+    fn sums() {
+        let insets = EdgeInsets::new(1, 2, 3, 4);
+        assert_eq!(insets.horizontal(), 6);
+        assert_eq!(insets.vertical(), 4);
+        assert_eq!(insets + EdgeInsets::all(1), EdgeInsets::new(2, 3, 4, 5));
     }
 }
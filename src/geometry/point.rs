@@ -115,6 +115,18 @@ where
     }
 }
 
+impl<T: Num> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T: Num> From<Point<T>> for (T, T) {
+    fn from(point: Point<T>) -> Self {
+        (point.x, point.y)
+    }
+}
+
 impl<T: Num> Point<T>
 where
     T: Copy,
@@ -244,9 +256,41 @@ where
 
     /// Returns the distance between this point and another.
     pub fn distance_to(&self, point: &Point<T>) -> T {
-        let x = self.x - point.x;
-        let y = self.y - point.y;
-        T::sqrt(x * x + y * y)
+        self.distance(*point)
+    }
+
+    /// Returns the distance between this point and another.
+    pub fn distance(&self, other: Point<T>) -> T {
+        T::sqrt(self.distance_squared(other))
+    }
+
+    /// Returns the squared distance between this point and another.
+    /// This avoids the square root when distances are only compared.
+    pub fn distance_squared(&self, other: Point<T>) -> T {
+        let x = self.x - other.x;
+        let y = self.y - other.y;
+        x * x + y * y
+    }
+
+    /// Returns the distance from the origin to this point.
+    pub fn magnitude(&self) -> T {
+        self.distance(Point::zero())
+    }
+
+    /// Returns the dot product of this point and another,
+    /// treating both as vectors.
+    pub fn dot(&self, other: Point<T>) -> T {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Linearly interpolates between this point and another by `t`.
+    /// A `t` of 0 returns this point and 1 returns the other point.
+    pub fn lerp(&self, other: Point<T>, t: T) -> Point<T> {
+        let lerp_value = |a: T, b: T| a * (T::one() - t) + b * t;
+        Point {
+            x: lerp_value(self.x, other.x),
+            y: lerp_value(self.y, other.y),
+        }
     }
 }
 
@@ -287,4 +331,41 @@ mod tests {
         let distance = point_a.distance_to(&point_b);
         assert_eq!(distance, 5.0);
     }
+
+    #[test]
+    fn test_distance() {
+        let origin = Point { x: 0.0, y: 0.0 };
+        let point = Point { x: 3.0, y: 4.0 };
+        assert_eq!(origin.distance(point), 5.0);
+        assert_eq!(point.distance(origin), 5.0);
+        assert_eq!(origin.distance_squared(point), 25.0);
+        assert_eq!(point.magnitude(), 5.0);
+    }
+
+    #[test]
+    fn test_dot() {
+        let point = Point { x: 2.0, y: 3.0 };
+        assert_eq!(point.dot(Point { x: 4.0, y: -1.0 }), 5.0);
+        assert_eq!(point.dot(Point { x: -3.0, y: 2.0 }), 0.0);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let start = Point { x: 0.0, y: 0.0 };
+        let end = Point { x: 10.0, y: 20.0 };
+        assert_eq!(start.lerp(end, 0.5), Point { x: 5.0, y: 10.0 });
+
+        let start = Point { x: 0.1, y: -7.3 };
+        let end = Point { x: 0.7, y: 1e8 };
+        assert_eq!(start.lerp(end, 0.0), start);
+        assert_eq!(start.lerp(end, 1.0), end);
+    }
+
+    #[test]
+    fn test_tuple_conversion() {
+        let point: Point<i32> = (3, -4).into();
+        assert_eq!(point, Point { x: 3, y: -4 });
+        let tuple: (i32, i32) = point.into();
+        assert_eq!(tuple, (3, -4));
+    }
 }
@@ -22,36 +22,43 @@ impl<T: Num> Rect<T> {
     }
 }
 
+impl<T> Rect<T>
+where
+    T: Num + Copy + AddAssign,
+{
+    /// Moves the rectangle by the given delta, keeping its size.
+    pub fn translate(&mut self, delta: Point<T>) {
+        self.origin.x += delta.x;
+        self.origin.y += delta.y;
+    }
+
+    /// Returns a copy of the rectangle moved by the given delta.
+    pub fn translated(&self, delta: Point<T>) -> Rect<T> {
+        let mut rect = *self;
+        rect.translate(delta);
+        rect
+    }
+}
+
 impl<T> Rect<T>
 where
     T: Num + Ord + PrimInt + Signed + AddAssign,
 {
     /// Creates a rectangle containing two points.
     pub fn containing(point_a: &Point<T>, point_b: &Point<T>) -> Self {
-        let mut top_left = Point::zero();
-        let mut bottom_right = Point::zero();
-
-        if point_a.x <= point_b.x {
-            top_left.x = point_a.x;
-            bottom_right.x = point_b.x;
-        } else {
-            top_left.x = point_a.x;
-            bottom_right.x = point_b.x;
-        }
-
-        if point_a.y <= point_b.y {
-            top_left.y = point_a.y;
-            bottom_right.y = point_b.y;
-        } else {
-            top_left.y = point_a.y;
-            bottom_right.y = point_b.y;
-        }
+        let top_left = Point {
+            x: cmp::min(point_a.x, point_b.x),
+            y: cmp::min(point_a.y, point_b.y),
+        };
+        let bottom_right = Point {
+            x: cmp::max(point_a.x, point_b.x),
+            y: cmp::max(point_a.y, point_b.y),
+        };
 
         let width = bottom_right.x - top_left.x;
         let height = bottom_right.y - top_left.y;
 
         let mut rect = Self::new(top_left.x, top_left.y, width, height);
-        rect.normalize();
         rect.ensure_non_zero_dimension();
         rect
     }
@@ -189,8 +196,8 @@ where
     pub fn inset_float(&self, insets: &EdgeInsets<T>) -> Self {
         let x = self.origin.x + insets.left;
         let y = self.origin.y + insets.top;
-        let width = self.size.width - insets.left - insets.right;
-        let height = self.size.height - insets.top - insets.bottom;
+        let width = self.size.width - insets.horizontal();
+        let height = self.size.height - insets.vertical();
         let mut result = Self::new(x, y, width, height);
         result.normalize_float();
         result
@@ -291,6 +298,30 @@ where
         let height = max_y - min_y;
         Rect::new(min_x, min_y, width, height)
     }
+
+    /// Returns the rectangle with its origin and size multiplied by
+    /// `factor`, scaling it about the coordinate space's origin.
+    /// The result is not normalized, so a negative factor will
+    /// give a negative width and height.
+    pub fn scaled(&self, factor: T) -> Rect<T> {
+        Rect::new(
+            self.origin.x * factor,
+            self.origin.y * factor,
+            self.size.width * factor,
+            self.size.height * factor,
+        )
+    }
+
+    /// Returns the rectangle scaled by `factor` while keeping `anchor`
+    /// in the same place. As with `scaled`, the result is not normalized.
+    pub fn scaled_about(&self, factor: T, anchor: Point<T>) -> Rect<T> {
+        Rect::new(
+            anchor.x + (self.origin.x - anchor.x) * factor,
+            anchor.y + (self.origin.y - anchor.y) * factor,
+            self.size.width * factor,
+            self.size.height * factor,
+        )
+    }
 }
 
 impl<T: Float> Rect<T> {
@@ -324,6 +355,16 @@ impl From<Rect<i32>> for Rect<f32> {
     }
 }
 
+impl Rect<i32> {
+    /// Returns an iterator over every integer point inside the normalized
+    /// rectangle, in row-major order. The maximum edges are excluded, so a
+    /// rectangle yields `width * height` points.
+    pub fn points(&self) -> impl Iterator<Item = Point<i32>> {
+        let (min_x, max_x) = (self.min_x(), self.max_x());
+        (self.min_y()..self.max_y()).flat_map(move |y| (min_x..max_x).map(move |x| Point { x, y }))
+    }
+}
+
 impl From<Rect<u32>> for Rect<i32> {
     fn from(value: Rect<u32>) -> Self {
         Self {
@@ -426,8 +467,8 @@ where
     pub fn inset(&self, insets: &EdgeInsets<T>) -> Self {
         let x = self.origin.x + insets.left;
         let y = self.origin.y + insets.top;
-        let width = self.size.width - insets.left - insets.right;
-        let height = self.size.height - insets.top - insets.bottom;
+        let width = self.size.width - insets.horizontal();
+        let height = self.size.height - insets.vertical();
         let mut result = Self::new(x, y, width, height);
         result.normalize();
         result
@@ -501,6 +542,33 @@ where
         let size_array = self.size.to_array();
         [origin_array, size_array]
     }
+
+    /// Returns the rectangle as a tuple of x, y, width and height.
+    pub fn into_tuple(&self) -> (T, T, T, T) {
+        (
+            self.origin.x,
+            self.origin.y,
+            self.size.width,
+            self.size.height,
+        )
+    }
+
+    /// Creates a rectangle from a tuple of x, y, width and height.
+    pub fn from_tuple((x, y, width, height): (T, T, T, T)) -> Self {
+        Rect::new(x, y, width, height)
+    }
+}
+
+impl<T: Num + Copy> From<(T, T, T, T)> for Rect<T> {
+    fn from(tuple: (T, T, T, T)) -> Self {
+        Rect::from_tuple(tuple)
+    }
+}
+
+impl<T: Num + Copy> From<Rect<T>> for (T, T, T, T) {
+    fn from(rect: Rect<T>) -> Self {
+        rect.into_tuple()
+    }
 }
 
 // SERIALISATION
@@ -530,6 +598,24 @@ mod tests {
         assert_eq!(result, Rect::new(9, 3, 1, 10));
     }
 
+    #[test]
+    fn test_containing_up_and_left() {
+        let point_a = Point { x: 10, y: 8 };
+        let point_b = Point { x: 4, y: 2 };
+
+        let result = Rect::containing(&point_a, &point_b);
+
+        assert_eq!(result.origin, point_b);
+        assert_eq!(
+            result.size,
+            Size {
+                width: 6,
+                height: 6
+            }
+        );
+        assert_eq!(result, Rect::containing(&point_b, &point_a));
+    }
+
     #[test]
     fn test_containing_float() {
         let point_a = Point { x: 12.3, y: 14.2 };
@@ -598,6 +684,55 @@ mod tests {
         assert!(rect.contains(point_outside) == false);
     }
 
+    #[test]
+    fn test_contains_rect() {
+        let rect = Rect::new(3, 4, 10, 23);
+        assert!(rect.contains_rect(&rect));
+        assert!(rect.contains_rect(&Rect::new(5, 6, 2, 2)));
+        assert!(rect.contains_rect(&Rect::new(7, 8, 0, 0)));
+
+        // Rectangles sharing an edge with the container are contained.
+        assert!(rect.contains_rect(&Rect::new(3, 4, 5, 5)));
+        assert!(rect.contains_rect(&Rect::new(8, 22, 5, 5)));
+        assert!(rect.contains_rect(&Rect::new(13, 27, -10, -23)));
+
+        assert!(!rect.contains_rect(&Rect::new(2, 4, 5, 5)));
+        assert!(!rect.contains_rect(&Rect::new(9, 22, 5, 6)));
+        assert!(!rect.contains_rect(&Rect::new(20, 30, 0, 0)));
+    }
+
+    #[test]
+    fn test_points() {
+        let rect = Rect::new(3, 4, 10, 23);
+        assert_eq!(rect.points().count(), 230);
+
+        let points: Vec<Point<i32>> = Rect::new(1, 2, -2, 2).points().collect();
+        assert_eq!(
+            points,
+            vec![
+                Point { x: -1, y: 2 },
+                Point { x: 0, y: 2 },
+                Point { x: -1, y: 3 },
+                Point { x: 0, y: 3 },
+            ]
+        );
+
+        assert_eq!(Rect::new(3, 4, 0, 23).points().count(), 0);
+        assert_eq!(Rect::new(3, 4, 10, 0).points().count(), 0);
+    }
+
+    #[test]
+    fn test_tuple_conversion() {
+        let rect = Rect::new(3, 4, 10, 23);
+        let tuple = rect.into_tuple();
+        assert_eq!(tuple, (3, 4, 10, 23));
+        assert_eq!(Rect::from_tuple(tuple), rect);
+
+        let rect = Rect::new(0.5, -1.0, 2.0, 3.5);
+        let tuple: (f32, f32, f32, f32) = rect.into();
+        assert_eq!(Rect::from(tuple), rect);
+    }
+
     #[test]
     fn test_to_json_array() {
         let rect = Rect::new(3, 4, 10, 23);
@@ -631,6 +766,26 @@ mod tests {
         assert_eq!(rect_a.union(&rect_b), expected);
 
         assert_eq!(rect_a.union(&rect_a), rect_a);
+
+        let rect = Rect::new(0, 0, 3, 3);
+        let other_rect = Rect::new(5, 5, 1, 1);
+        assert_eq!(rect.union(&other_rect), Rect::new(0, 0, 6, 6));
+        assert_eq!(other_rect.union(&rect), Rect::new(0, 0, 6, 6));
+
+        // Negative dimensions are measured from the origin backwards.
+        let negative_rect = Rect::new(6, 6, -1, -1);
+        assert_eq!(rect.union(&negative_rect), Rect::new(0, 0, 6, 6));
+    }
+
+    #[test]
+    fn test_translated() {
+        let rect = Rect::new(3, 4, 10, 23);
+        let result = rect.translated(Point { x: -3, y: -4 });
+        assert_eq!(result, Rect::new(0, 0, 10, 23));
+
+        let mut rect = Rect::new(3.0, 4.0, 10.0, 23.0);
+        rect.translate(Point { x: -3.0, y: -4.0 });
+        assert_eq!(rect, Rect::new(0.0, 0.0, 10.0, 23.0));
     }
 
     #[test]
@@ -646,6 +801,19 @@ mod tests {
         assert_eq!(new_rect.size.height, 5);
     }
 
+    #[test]
+    fn test_scaled() {
+        let rect = Rect::new(2.0, 2.0, 4.0, 4.0);
+        assert_eq!(rect.scaled(2.0), Rect::new(4.0, 4.0, 8.0, 8.0));
+
+        let result = rect.scaled_about(2.0, rect.midpoint());
+        assert_eq!(result, Rect::new(0.0, 0.0, 8.0, 8.0));
+        assert_eq!(result.midpoint(), rect.midpoint());
+
+        let result = rect.scaled_about(-1.0, rect.midpoint());
+        assert_eq!(result, Rect::new(6.0, 6.0, -4.0, -4.0));
+    }
+
     #[test]
     fn test_midpoint() {
         let rect = Rect::new(3.0, 5.0, 7.0, 9.0);
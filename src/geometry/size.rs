@@ -1,6 +1,6 @@
 use core::ops::Add;
 use num_traits::{Float, Num, One, Zero};
-use std::ops::Mul;
+use std::ops::{Div, Mul};
 
 #[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
 /// Represents a size.
@@ -29,6 +29,33 @@ impl<T: Float> Size<T> {
             height: self.height.round().to_i32().unwrap(),
         }
     }
+
+    /// Returns the size scaled to fit entirely within the bounds,
+    /// preserving the aspect ratio. A size with a zero dimension
+    /// returns a zero size.
+    pub fn aspect_fit(&self, bounds: Size<T>) -> Size<T> {
+        self.aspect_scaled(bounds, Float::min)
+    }
+
+    /// Returns the size scaled to cover the bounds entirely,
+    /// preserving the aspect ratio. A size with a zero dimension
+    /// returns a zero size.
+    pub fn aspect_fill(&self, bounds: Size<T>) -> Size<T> {
+        self.aspect_scaled(bounds, Float::max)
+    }
+
+    /// Returns the size scaled by the horizontal or vertical scale
+    /// needed to match the bounds, as chosen by `choose`.
+    fn aspect_scaled(&self, bounds: Size<T>, choose: fn(T, T) -> T) -> Size<T> {
+        if self.width.is_zero() || self.height.is_zero() {
+            return Size::zero();
+        }
+        let scale = choose(bounds.width / self.width, bounds.height / self.height);
+        Size {
+            width: self.width * scale,
+            height: self.height * scale,
+        }
+    }
 }
 
 impl<T> One for Size<T>
@@ -57,6 +84,34 @@ where
     }
 }
 
+impl<T> Mul<T> for Size<T>
+where
+    T: Copy + Num + Mul<Output = T>,
+{
+    type Output = Self;
+
+    fn mul(self, scalar: T) -> Self {
+        Self {
+            width: self.width * scalar,
+            height: self.height * scalar,
+        }
+    }
+}
+
+impl<T> Div<T> for Size<T>
+where
+    T: Copy + Num + Div<Output = T>,
+{
+    type Output = Self;
+
+    fn div(self, scalar: T) -> Self {
+        Self {
+            width: self.width / scalar,
+            height: self.height / scalar,
+        }
+    }
+}
+
 impl<T> Add for Size<T>
 where
     T: Num + Add,
@@ -206,6 +261,18 @@ where
     }
 }
 
+impl<T: Num> From<(T, T)> for Size<T> {
+    fn from((width, height): (T, T)) -> Self {
+        Size { width, height }
+    }
+}
+
+impl<T: Num> From<Size<T>> for (T, T) {
+    fn from(size: Size<T>) -> Self {
+        (size.width, size.height)
+    }
+}
+
 impl<T: Num> Size<T>
 where
     T: Copy,
@@ -228,3 +295,74 @@ where
         serde_json::to_string(&array)
     }
 }
+
+// MARK: Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_arithmetic() {
+        let size = Size {
+            width: 4,
+            height: 6,
+        };
+        assert_eq!(
+            size * 2,
+            Size {
+                width: 8,
+                height: 12,
+            }
+        );
+        assert_eq!(
+            size / 2,
+            Size {
+                width: 2,
+                height: 3,
+            }
+        );
+        assert_eq!(size * Size::one(), size);
+    }
+
+    #[test]
+    fn tuple_conversion() {
+        let size: Size<u32> = (16, 9).into();
+        assert_eq!(
+            size,
+            Size {
+                width: 16,
+                height: 9,
+            }
+        );
+        let tuple: (u32, u32) = size.into();
+        assert_eq!(tuple, (16, 9));
+    }
+
+    #[test]
+    fn aspect_fit_and_fill() {
+        let size = Size {
+            width: 1920.0,
+            height: 1080.0,
+        };
+        let bounds = Size {
+            width: 100.0,
+            height: 100.0,
+        };
+
+        let fit = size.aspect_fit(bounds);
+        assert_eq!(fit.width, 100.0);
+        assert!((fit.height - 56.25).abs() < 0.001);
+
+        let fill = size.aspect_fill(bounds);
+        assert!((fill.width - 177.78).abs() < 0.01);
+        assert_eq!(fill.height, 100.0);
+
+        let empty = Size {
+            width: 0.0,
+            height: 1080.0,
+        };
+        assert_eq!(empty.aspect_fit(bounds), Size::zero());
+        assert_eq!(empty.aspect_fill(bounds), Size::zero());
+    }
+}
@@ -1,19 +1,33 @@
+pub use contact_sheet::*;
+pub use delta_frames::*;
+pub use lut::*;
 pub use mask_operations::*;
+pub use shared::*;
 use tiff::encoder::compression::Compression;
 use tiff::encoder::{colortype, TiffEncoder};
 
 use std::cmp::min;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::Cursor;
 use std::path::Path;
 
 use image::{DynamicImage, ImageFormat, RgbaImage};
 
 use crate::composite::{self, Layer};
-use crate::{BlendMode, Color, Mask, Point, Rect, Size};
+use crate::{BlendMode, Color, EdgeInsets, Mask, Point, Rect, Size};
 
+mod analysis;
 mod colors;
+mod contact_sheet;
 pub mod cv;
+mod delta_frames;
+mod drawing;
+pub mod filters;
+mod lut;
 mod mask_operations;
+mod rle;
+mod seamless;
+mod shared;
 pub mod transformation;
 
 /// The representation of an image for graphics manipulation.
@@ -51,17 +65,58 @@ impl Image {
         }
     }
 
+    /// Creates an empty image of a given size where `bytes_per_row` is
+    /// padded up to a multiple of the alignment, in bytes.
+    pub fn empty_aligned(size: Size<u32>, alignment: u32) -> Self {
+        let alignment = alignment.max(1);
+        let bytes_per_row = (size.width * 4).div_ceil(alignment) * alignment;
+        let data_size = (bytes_per_row * size.height) as usize;
+        let data = vec![0u8; data_size];
+        Image {
+            data,
+            size,
+            bytes_per_row,
+        }
+    }
+
     /// Creates an image with a colour.
     pub fn color(color: &Color, size: Size<u32>) -> Image {
         let bytes_per_row = size.width * 4;
-        let data_size = (size.width * size.height) as usize;
-        let data = vec![color.red, color.green, color.blue, color.alpha].repeat(data_size);
+        // Sized in `usize` so that large images don’t overflow.
+        let row = [color.red, color.green, color.blue, color.alpha].repeat(size.width as usize);
+        let data = row.repeat(size.height as usize);
+        debug_assert_eq!(data.len(), bytes_per_row as usize * size.height as usize);
         Image {
             data,
             size,
             bytes_per_row,
         }
     }
+
+    /// Creates an image of a given size filled with a tile, repeated in
+    /// both directions from the top left corner. The tiles on the right
+    /// and bottom edges are cut off if the size isn’t a multiple of the
+    /// tile size. The image is transparent if the tile is empty.
+    pub fn tiled(tile: &Image, size: Size<u32>) -> Image {
+        let mut image = Image::empty(size);
+        if tile.size.width == 0 || tile.size.height == 0 {
+            return image;
+        }
+
+        let tile_row_length = tile.size.width as usize * 4;
+        let row_length = size.width as usize * 4;
+        for y in 0..size.height as usize {
+            let tile_y = y % tile.size.height as usize;
+            let tile_start = tile_y * tile.bytes_per_row as usize;
+            let tile_row = &tile.data[tile_start..tile_start + tile_row_length];
+            let row_start = y * image.bytes_per_row as usize;
+            let row = &mut image.data[row_start..row_start + row_length];
+            for chunk in row.chunks_mut(tile_row_length) {
+                chunk.copy_from_slice(&tile_row[..chunk.len()]);
+            }
+        }
+        image
+    }
 }
 
 // IMAGE FILE INTEGRATION
@@ -168,9 +223,68 @@ impl Image {
     }
 }
 
+// RAW DATA
+
+impl Image {
+    /// Returns a tightly packed copy of the image data with the colour
+    /// channels premultiplied by the alpha channel. The image itself is
+    /// not modified.
+    pub fn premultiplied_bytes(&self) -> Vec<u8> {
+        let row_length = self.size.width as usize * 4;
+        let mut output = Vec::with_capacity(row_length * self.size.height as usize);
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            for pixel in self.data[row_start..row_start + row_length].chunks_exact(4) {
+                let alpha = pixel[3] as u32;
+                let premultiply = |value: u8| ((value as u32 * alpha + 127) / 255) as u8;
+                output.push(premultiply(pixel[0]));
+                output.push(premultiply(pixel[1]));
+                output.push(premultiply(pixel[2]));
+                output.push(pixel[3]);
+            }
+        }
+        output
+    }
+
+    /// Returns an iterator over the rows of the image, from top to bottom.
+    /// Each row contains the RGBA data of the visible pixels only, without
+    /// any padding at the end of the row.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        let row_length = self.size.width as usize * 4;
+        (0..self.size.height as usize).map(move |y| {
+            let row_start = y * self.bytes_per_row as usize;
+            &self.data[row_start..row_start + row_length]
+        })
+    }
+
+    /// Returns an iterator over the colours of the pixels in the image,
+    /// row by row from the top left corner.
+    pub fn pixels(&self) -> impl Iterator<Item = Color> + '_ {
+        self.rows().flat_map(|row| {
+            row.chunks_exact(4)
+                .map(|pixel| Color::from(<[u8; 4]>::try_from(pixel).unwrap()))
+        })
+    }
+}
+
 // EQUALITY
 
 impl Image {
+    /// Returns a hash of the image’s size and visible pixels, ignoring any
+    /// row padding. Images that are equal have the same hash. The hash is
+    /// only stable within a single build, so it shouldn’t be persisted.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.size.width.hash(&mut hasher);
+        self.size.height.hash(&mut hasher);
+        let row_length = self.size.width as usize * 4;
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            hasher.write(&self.data[row_start..row_start + row_length]);
+        }
+        hasher.finish()
+    }
+
     /// Returns whether or not the image is transparent.
     pub fn is_transparent(&self) -> bool {
         for y in 0..self.size.height as usize {
@@ -234,36 +348,164 @@ impl Image {
 
         true
     }
+
+    /// Returns the smallest region containing every pixel that differs
+    /// between this image and another of the same size, or `None` if the
+    /// images are identical.
+    pub fn diff_region(&self, other: &Image) -> anyhow::Result<Option<Rect<i32>>> {
+        if self.size != other.size {
+            anyhow::bail!("The images must be the same size to be compared.");
+        }
+
+        let mut region: Option<Rect<i32>> = None;
+        for y in 0..self.size.height as i32 {
+            for x in 0..self.size.width as i32 {
+                let location = Point { x, y };
+                if self.pixel_color(location) == other.pixel_color(location) {
+                    continue;
+                }
+                let pixel_rect = Rect::new(x, y, 1, 1);
+                region = Some(match region {
+                    Some(region) => region.union(&pixel_rect),
+                    None => pixel_rect,
+                });
+            }
+        }
+        Ok(region)
+    }
+
+    /// Returns a copy of this image where every pixel that differs from
+    /// the corresponding pixel in the other image is tinted halfway towards
+    /// the highlight colour. This is meant to help debug image comparisons.
+    pub fn difference_overlay(&self, other: &Image, highlight: &Color) -> anyhow::Result<Image> {
+        if self.size != other.size {
+            anyhow::bail!("The images must be the same size to be compared.");
+        }
+
+        let mut result = self.clone();
+        for y in 0..self.size.height as i32 {
+            for x in 0..self.size.width as i32 {
+                let location = Point { x, y };
+                let (Some(color), Some(other_color)) =
+                    (self.pixel_color(location), other.pixel_color(location))
+                else {
+                    continue;
+                };
+                if color == other_color {
+                    continue;
+                }
+                let tint =
+                    |value: u8, highlight: u8| (value as u16 + highlight as u16).div_ceil(2) as u8;
+                let tinted = Color {
+                    red: tint(color.red, highlight.red),
+                    green: tint(color.green, highlight.green),
+                    blue: tint(color.blue, highlight.blue),
+                    alpha: color.alpha.max(highlight.alpha),
+                };
+                result.set_pixel_color(tinted, location.into());
+            }
+        }
+        Ok(result)
+    }
 }
 
 // CROPPING
 
 impl Image {
-    /// Crops an image to fit a given size, keeping the image at the centre.
-    /// The size can be larger or smaller than the image in either dimension.
+    /// Crops an image to a given size, taking the pixels from the region
+    /// of the original image whose top left corner is at `offset`.
+    /// The size can be larger or smaller than the image in either dimension,
+    /// and the offset can be negative. Any part of the region outside of
+    /// the original image is transparent.
     pub fn crop_with_offset(&mut self, size: Size<u32>, offset: Point<i32>) -> anyhow::Result<()> {
-        let origin = offset;
-        if size == self.size && origin == Point::zero() {
+        if size == self.size && offset == Point::zero() {
             return Ok(());
         }
 
-        let mut image_buffer = self.to_image_buffer()?;
+        let mut result = Image::empty(size);
+        let location = Point {
+            x: -offset.x,
+            y: -offset.y,
+        };
+        result.draw_image_over(self, location);
+        *self = result;
 
-        // TODO: Need to handle negative offsets.
-        let result = image::imageops::crop(
-            &mut image_buffer,
-            offset.x as u32,
-            offset.y as u32,
-            size.width,
-            size.height,
-        );
+        Ok(())
+    }
 
-        let result = Image::from_rgba_image(result.to_image())?;
-        self.data = result.data;
-        self.size = result.size;
-        self.bytes_per_row = result.bytes_per_row;
+    /// Returns a new image containing the region of this image. The region
+    /// is clamped to the bounds of the image first, and an error is
+    /// returned if nothing is left. Rows are copied whole, so this is much
+    /// faster than `subimage`.
+    pub fn cropped(&self, region: Rect<i32>) -> anyhow::Result<Image> {
+        let bounds = Rect {
+            origin: Point::zero(),
+            size: self.size.into(),
+        };
+        let Some(region) = region
+            .intersection(&bounds)
+            .filter(|region| region.size.width > 0 && region.size.height > 0)
+        else {
+            anyhow::bail!("The region does not overlap the image.");
+        };
 
-        Ok(())
+        let mut result = Image::empty(region.size.into());
+        let row_length = region.size.width as usize * 4;
+        for y in 0..region.size.height as usize {
+            let source_start = (region.origin.y as usize + y) * self.bytes_per_row as usize
+                + region.origin.x as usize * 4;
+            let target_start = y * result.bytes_per_row as usize;
+            result.data[target_start..target_start + row_length]
+                .copy_from_slice(&self.data[source_start..source_start + row_length]);
+        }
+        Ok(result)
+    }
+
+    /// Slices the image into a grid of `tile` sized cells, and returns the
+    /// cells that contain at least one pixel that isn’t fully transparent,
+    /// along with their column and row in the grid. Cells on the right and
+    /// bottom edges are smaller if the image size isn’t a multiple of the
+    /// tile size. Returns nothing if either dimension of the tile is zero.
+    pub fn nonempty_tiles(&self, tile: Size<u32>) -> Vec<(Point<u32>, Image)> {
+        if tile.width == 0 || tile.height == 0 {
+            return Vec::new();
+        }
+
+        let mut tiles = Vec::new();
+        for row in 0..self.size.height.div_ceil(tile.height) {
+            for column in 0..self.size.width.div_ceil(tile.width) {
+                let region = Rect::new(
+                    (column * tile.width) as i32,
+                    (row * tile.height) as i32,
+                    tile.width as i32,
+                    tile.height as i32,
+                );
+                let Ok(image) = self.cropped(region) else {
+                    continue;
+                };
+                if image.data.chunks_exact(4).all(|pixel| pixel[3] == 0) {
+                    continue;
+                }
+                tiles.push((Point { x: column, y: row }, image));
+            }
+        }
+        tiles
+    }
+
+    /// Returns a copy of the image with a transparent border added,
+    /// growing it by the insets on each side.
+    pub fn padded(&self, insets: EdgeInsets<u32>) -> Image {
+        let size = Size {
+            width: self.size.width + insets.horizontal(),
+            height: self.size.height + insets.vertical(),
+        };
+        let mut result = Image::empty(size);
+        let location = Point {
+            x: insets.left as i32,
+            y: insets.top as i32,
+        };
+        result.draw_image_over(self, location);
+        result
     }
 }
 
@@ -322,6 +564,40 @@ impl Image {
         self.data[offset + 2] = color.blue;
         self.data[offset + 3] = color.alpha;
     }
+
+    /// Sets the colour of the pixel at a given point, but only if its
+    /// current colour is within `tolerance` of `expected`. Points outside
+    /// of the image are ignored.
+    pub fn set_pixel_if(
+        &mut self,
+        location: Point<u32>,
+        new: &Color,
+        expected: &Color,
+        tolerance: u8,
+    ) {
+        if location.x >= self.size.width || location.y >= self.size.height {
+            return;
+        }
+        let offset = self.bytes_per_row as usize * location.y as usize + location.x as usize * 4;
+        let pixel: [u8; 4] = self.data[offset..offset + 4].try_into().unwrap();
+        if !Color::from(pixel).is_within_tolerance_of(expected, tolerance) {
+            return;
+        }
+        self.data[offset..offset + 4].copy_from_slice(&<[u8; 4]>::from(new));
+    }
+
+    /// Returns whether the pixel at a point has an alpha of at least
+    /// `alpha_threshold`, for hit testing. Points outside of the image
+    /// are never hit.
+    pub fn is_hit(&self, point: Point<i32>, alpha_threshold: u8) -> bool {
+        // `pixel_color` allows points on the far edges, so the bounds
+        // are checked here.
+        if point.x >= self.size.width as i32 || point.y >= self.size.height as i32 {
+            return false;
+        }
+        self.pixel_color(point)
+            .is_some_and(|color| color.alpha >= alpha_threshold)
+    }
 }
 
 // TRIMMING
@@ -337,6 +613,26 @@ impl Image {
         self.trim_in_container(container)
     }
 
+    /// Returns whether the top, right, bottom and left edges of the image
+    /// are fully transparent, in that order. Only the outermost row or
+    /// column of pixels is checked for each edge. An empty image has no
+    /// opaque pixels, so all of its edges count as transparent.
+    pub fn transparent_edges(&self) -> (bool, bool, bool, bool) {
+        let width = self.size.width as usize;
+        let height = self.size.height as usize;
+        if width == 0 || height == 0 {
+            return (true, true, true, true);
+        }
+        let is_clear =
+            |x: usize, y: usize| self.data[y * self.bytes_per_row as usize + x * 4 + 3] == 0;
+
+        let top = (0..width).all(|x| is_clear(x, 0));
+        let right = (0..height).all(|y| is_clear(width - 1, y));
+        let bottom = (0..width).all(|x| is_clear(x, height - 1));
+        let left = (0..height).all(|y| is_clear(0, y));
+        (top, right, bottom, left)
+    }
+
     /// Trims the transparent pixels from the edge of the image and returns
     /// the new bounding rect relative to the original.
     pub fn trim_in_container(&mut self, container: Rect<i32>) -> anyhow::Result<Rect<i32>> {
@@ -501,6 +797,220 @@ impl Image {
     }
 }
 
+impl Image {
+    /// Makes a best guess at the background colour of the image by
+    /// picking the most common colour among the four corners.
+    /// Ties are resolved in favour of the top left corner.
+    pub fn guess_background_color(&self) -> Option<Color> {
+        if self.size.width == 0 || self.size.height == 0 {
+            return None;
+        }
+
+        let max_x = self.size.width as i32 - 1;
+        let max_y = self.size.height as i32 - 1;
+        let corners: Vec<Color> = [
+            Point { x: 0, y: 0 },
+            Point { x: max_x, y: 0 },
+            Point { x: 0, y: max_y },
+            Point { x: max_x, y: max_y },
+        ]
+        .into_iter()
+        .filter_map(|point| self.pixel_color(point))
+        .collect();
+
+        let mut best: Option<(&Color, usize)> = None;
+        for corner in corners.iter() {
+            let count = corners.iter().filter(|color| *color == corner).count();
+            if best.is_none_or(|(_, best_count)| count > best_count) {
+                best = Some((corner, count));
+            }
+        }
+        best.map(|(color, _)| color.clone())
+    }
+
+    /// Trims the edges of the image where every pixel is within
+    /// `tolerance` of the supplied colour, and returns the new bounding
+    /// rect relative to the original.
+    pub fn trim_color(&mut self, color: &Color, tolerance: u8) -> anyhow::Result<Rect<i32>> {
+        self.trim_matching(|pixel| pixel.is_within_tolerance_of(color, tolerance))
+    }
+
+    /// Trims the edges of the image where every pixel is within
+    /// `tolerance` of a solid matte colour, such as the white background
+    /// of an exported logo, and returns the new bounding rect relative
+    /// to the original.
+    ///
+    /// When `defringe` is set, the remaining pixels within `tolerance` of
+    /// the matte that are connected to the edges are made transparent, and
+    /// the anti-aliased pixels along the edges of the content have the
+    /// matte removed from them. This assumes those pixels were composited
+    /// over the matte, and recovers the foreground colour along with the
+    /// alpha it was drawn with.
+    pub fn trim_matte(
+        &mut self,
+        matte: &Color,
+        tolerance: u8,
+        defringe: bool,
+    ) -> anyhow::Result<Rect<i32>> {
+        let rect = self.trim_color(matte, tolerance)?;
+        if defringe {
+            self.remove_matte(matte, tolerance);
+        }
+        Ok(rect)
+    }
+
+    /// Makes the pixels within `tolerance` of the matte that are connected
+    /// to the edges of the image transparent, and un-blends the matte from
+    /// the pixels next to them. Matte coloured pixels enclosed by the
+    /// content are left alone. Locations outside of the image count as
+    /// matte.
+    fn remove_matte(&mut self, matte: &Color, tolerance: u8) {
+        let original = self.clone();
+        let width = self.size.width as i32;
+        let height = self.size.height as i32;
+        let index = |x: i32, y: i32| (y * width + x) as usize;
+        let matches_matte = |x: i32, y: i32| {
+            original
+                .pixel_color(Point { x, y })
+                .is_some_and(|color| color.is_within_tolerance_of(matte, tolerance))
+        };
+
+        // Flood the matte inwards from the edges of the image.
+        let mut is_background = vec![false; (width * height) as usize];
+        let mut stack: Vec<(i32, i32)> = (0..width)
+            .flat_map(|x| [(x, 0), (x, height - 1)])
+            .chain((0..height).flat_map(|y| [(0, y), (width - 1, y)]))
+            .collect();
+        while let Some((x, y)) = stack.pop() {
+            if x < 0 || y < 0 || x >= width || y >= height {
+                continue;
+            }
+            if is_background[index(x, y)] || !matches_matte(x, y) {
+                continue;
+            }
+            is_background[index(x, y)] = true;
+            stack.extend([(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]);
+        }
+
+        let is_matte = |x: i32, y: i32| {
+            x < 0 || y < 0 || x >= width || y >= height || is_background[index(x, y)]
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let location = Point { x, y };
+                if is_matte(x, y) {
+                    self.set_pixel_color(Color::CLEAR, location.into());
+                    continue;
+                }
+                let is_edge = (-1..=1)
+                    .flat_map(|y_offset| (-1..=1).map(move |x_offset| (x_offset, y_offset)))
+                    .any(|(x_offset, y_offset)| is_matte(x + x_offset, y + y_offset));
+                if is_edge {
+                    let color = original.pixel_color(location).unwrap();
+                    self.set_pixel_color(unblend(&color, matte), location.into());
+                }
+            }
+        }
+    }
+
+    /// Trims the border of the image, automatically detecting the border
+    /// colour. If the border is transparent then any pixel with an alpha
+    /// within `tolerance` of zero is trimmed, otherwise any pixel within
+    /// `tolerance` of the border colour is trimmed.
+    /// Returns the new bounding rect relative to the original.
+    pub fn smart_trim(&mut self, tolerance: u8) -> anyhow::Result<Rect<i32>> {
+        let background = self
+            .guess_background_color()
+            .ok_or(anyhow::anyhow!("Unable to detect the background colour."))?;
+        if background.alpha == 0 {
+            self.trim_matching(|pixel| pixel.alpha <= tolerance)
+        } else {
+            self.trim_color(&background, tolerance)
+        }
+    }
+
+    /// Trims the edges of the image where every pixel satisfies the predicate.
+    fn trim_matching<F>(&mut self, is_background: F) -> anyhow::Result<Rect<i32>>
+    where
+        F: Fn(&Color) -> bool,
+    {
+        let width = self.size.width as i32;
+        let height = self.size.height as i32;
+
+        let row_is_background = |image: &Image, y: i32, min_x: i32, max_x: i32| {
+            (min_x..max_x).all(|x| {
+                image
+                    .pixel_color(Point { x, y })
+                    .is_some_and(|color| is_background(&color))
+            })
+        };
+        let column_is_background = |image: &Image, x: i32, min_y: i32, max_y: i32| {
+            (min_y..max_y).all(|y| {
+                image
+                    .pixel_color(Point { x, y })
+                    .is_some_and(|color| is_background(&color))
+            })
+        };
+
+        let Some(top) = (0..height).find(|&y| !row_is_background(self, y, 0, width)) else {
+            anyhow::bail!("The image only contains background pixels.");
+        };
+        let bottom = (top..height)
+            .rev()
+            .find(|&y| !row_is_background(self, y, 0, width))
+            .unwrap_or(top)
+            + 1;
+        let left = (0..width)
+            .find(|&x| !column_is_background(self, x, top, bottom))
+            .unwrap_or(0);
+        let right = (left..width)
+            .rev()
+            .find(|&x| !column_is_background(self, x, top, bottom))
+            .unwrap_or(left)
+            + 1;
+
+        let rect = Rect::new(left, top, right - left, bottom - top);
+        self.crop_with_offset(rect.size.into(), rect.origin)?;
+        Ok(rect)
+    }
+}
+
+/// Recovers the foreground colour of a pixel that was composited over
+/// a matte colour, using the lowest alpha that can produce the pixel.
+fn unblend(color: &Color, matte: &Color) -> Color {
+    let max = u8::MAX as f32;
+    let channels = [
+        (color.red as f32, matte.red as f32),
+        (color.green as f32, matte.green as f32),
+        (color.blue as f32, matte.blue as f32),
+    ];
+    let alpha = channels
+        .iter()
+        .map(|&(value, matte)| {
+            if value > matte {
+                (value - matte) / (max - matte)
+            } else if value < matte {
+                (matte - value) / matte
+            } else {
+                0.0
+            }
+        })
+        .fold(0.0, f32::max);
+    if alpha <= 0.0 {
+        return Color::CLEAR;
+    }
+
+    let foreground = |(value, matte): (f32, f32)| {
+        (matte + (value - matte) / alpha).round().clamp(0.0, max) as u8
+    };
+    Color {
+        red: foreground(channels[0]),
+        green: foreground(channels[1]),
+        blue: foreground(channels[2]),
+        alpha: (alpha * color.alpha as f32).round() as u8,
+    }
+}
+
 // PIXEL REPLACEMENT
 
 impl Image {
@@ -584,3 +1094,92 @@ impl Image {
         Ok(result)
     }
 }
+
+// MATTES
+
+impl Image {
+    /// Replaces the alpha of every pixel with the Rec. 709 luma of the
+    /// pixel at the same location in a greyscale matte, so white areas of
+    /// the matte are opaque and black areas are transparent. When `invert`
+    /// is set, black areas are opaque instead. The matte’s own alpha is
+    /// not used. Returns an error if the matte is a different size.
+    pub fn apply_luma_matte(&mut self, matte: &Image, invert: bool) -> anyhow::Result<()> {
+        if matte.size != self.size {
+            anyhow::bail!("The matte must be the same size as the image.");
+        }
+
+        for y in 0..self.size.height as usize {
+            for x in 0..self.size.width as usize {
+                let matte_offset = y * matte.bytes_per_row as usize + x * 4;
+                let matte_pixel: [u8; 4] = matte.data[matte_offset..matte_offset + 4]
+                    .try_into()
+                    .unwrap();
+                let luma = Color::from(matte_pixel).grayscale().red;
+                let alpha = if invert { u8::MAX - luma } else { luma };
+                self.data[y * self.bytes_per_row as usize + x * 4 + 3] = alpha;
+            }
+        }
+        Ok(())
+    }
+}
+
+// VIEWS
+
+/// A read-only view of a region of an image, which reads from the
+/// image’s buffer without copying it. Locations are relative to the
+/// origin of the region.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageView<'a> {
+    /// The image the view reads from.
+    image: &'a Image,
+    /// The region of the image covered by the view.
+    rect: Rect<i32>,
+}
+
+impl Image {
+    /// Returns a read-only view of a region of the image. The region is
+    /// normalized and limited to the bounds of the image.
+    pub fn view(&self, region: Rect<i32>) -> ImageView<'_> {
+        let bounds = Rect {
+            origin: Point::zero(),
+            size: self.size.into(),
+        };
+        let rect = region.intersection(&bounds).unwrap_or(Rect::zero());
+        ImageView { image: self, rect }
+    }
+}
+
+impl ImageView<'_> {
+    /// Returns the region of the image covered by the view.
+    pub fn rect(&self) -> Rect<i32> {
+        self.rect
+    }
+
+    /// Returns the size of the view.
+    pub fn size(&self) -> Size<u32> {
+        self.rect.size.into()
+    }
+
+    /// Returns the colour of the pixel at a location in the view,
+    /// or `None` if the location is outside of the view.
+    pub fn pixel_color(&self, location: Point<i32>) -> Option<Color> {
+        if location.x < 0
+            || location.y < 0
+            || location.x >= self.rect.size.width
+            || location.y >= self.rect.size.height
+        {
+            return None;
+        }
+        self.image.pixel_color(location + self.rect.origin)
+    }
+
+    /// Returns an iterator over the locations and colours of the pixels
+    /// in the view, in row-major order. Locations are relative to the view.
+    pub fn pixels(&self) -> impl Iterator<Item = (Point<i32>, Color)> + '_ {
+        let origin = self.rect.origin;
+        self.rect.points().filter_map(move |point| {
+            let color = self.image.pixel_color(point)?;
+            Some((point - origin, color))
+        })
+    }
+}
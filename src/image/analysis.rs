@@ -0,0 +1,450 @@
+use std::collections::BTreeMap;
+
+use crate::{Point, Rect, Size};
+
+use super::Image;
+
+impl Image {
+    /// Returns the bounding boxes of the transparent regions that are
+    /// completely enclosed by non-transparent pixels, such as the interior
+    /// of a closed outline. Transparent regions that can be reached from
+    /// the edge of the image are not included.
+    pub fn enclosed_transparent_regions(&self) -> Vec<Rect<i32>> {
+        let width = self.size.width as usize;
+        let height = self.size.height as usize;
+        let transparent: Vec<bool> = (0..height)
+            .flat_map(|y| {
+                let row_start = y * self.bytes_per_row as usize;
+                (0..width).map(move |x| self.data[row_start + x * 4 + 3] == 0)
+            })
+            .collect();
+        let mut visited = vec![false; transparent.len()];
+
+        // Mark everything reachable from the border first.
+        for y in 0..height {
+            for x in 0..width {
+                let is_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                if is_border && transparent[y * width + x] && !visited[y * width + x] {
+                    fill_region(&transparent, &mut visited, self.size, Point { x, y });
+                }
+            }
+        }
+
+        let mut regions = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if transparent[y * width + x] && !visited[y * width + x] {
+                    let region = fill_region(&transparent, &mut visited, self.size, Point { x, y });
+                    regions.push(region);
+                }
+            }
+        }
+        regions
+    }
+
+    /// Returns the vertices of the convex hull of the non-transparent
+    /// pixels, in pixel coordinates. The vertices are ordered clockwise,
+    /// as displayed, starting from the top-most of the left-most pixels.
+    /// Returns an empty vector if the image is fully transparent.
+    pub fn opaque_convex_hull(&self) -> Vec<Point<i32>> {
+        // Only the left-most and right-most pixels of each row can be
+        // on the hull.
+        let mut points = Vec::new();
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let is_opaque = |x: &usize| self.data[row_start + x * 4 + 3] != 0;
+            let width = self.size.width as usize;
+            let Some(min_x) = (0..width).find(is_opaque) else {
+                continue;
+            };
+            let max_x = (0..width).rev().find(is_opaque).unwrap_or(min_x);
+            points.push(Point {
+                x: min_x as i32,
+                y: y as i32,
+            });
+            if max_x != min_x {
+                points.push(Point {
+                    x: max_x as i32,
+                    y: y as i32,
+                });
+            }
+        }
+        points.sort_by_key(|point| (point.x, point.y));
+        if points.len() < 3 {
+            return points;
+        }
+
+        // Andrew’s monotone chain.
+        let cross = |o: &Point<i32>, a: &Point<i32>, b: &Point<i32>| {
+            (a.x - o.x) as i64 * (b.y - o.y) as i64 - (a.y - o.y) as i64 * (b.x - o.x) as i64
+        };
+        let mut hull: Vec<Point<i32>> = Vec::with_capacity(points.len() * 2);
+        for pass in [points.clone(), points.into_iter().rev().collect()] {
+            let start = hull.len();
+            for point in pass {
+                while hull.len() >= start + 2
+                    && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], &point) <= 0
+                {
+                    hull.pop();
+                }
+                hull.push(point);
+            }
+            // The last point of each chain is the first point of the next.
+            hull.pop();
+        }
+        hull
+    }
+
+    /// Traces the outlines of the regions whose alpha is greater than the
+    /// threshold, returning one closed polyline per outline. Holes inside
+    /// a region have their own outlines.
+    ///
+    /// The points lie on pixel corners, so a single pixel at `(x, y)` is
+    /// outlined by `(x, y)`, `(x + 1, y)`, `(x + 1, y + 1)` and `(x, y + 1)`.
+    /// Outer outlines run clockwise, as displayed, and holes run
+    /// anticlockwise. Diagonally touching pixels are treated as separate
+    /// regions, and only the corners of each outline are included.
+    pub fn alpha_contour(&self, threshold: u8) -> Vec<Vec<Point<i32>>> {
+        let width = self.size.width as i32;
+        let height = self.size.height as i32;
+        let is_inside = |x: i32, y: i32| {
+            x >= 0
+                && y >= 0
+                && x < width
+                && y < height
+                && self.data[y as usize * self.bytes_per_row as usize + x as usize * 4 + 3]
+                    > threshold
+        };
+
+        // Collect the pixel edges between inside and outside pixels,
+        // directed so that the inside is always on the right.
+        let mut edges: BTreeMap<(i32, i32), Vec<(i32, i32)>> = BTreeMap::new();
+        let mut add_edge = |from: (i32, i32), to: (i32, i32)| {
+            // Keyed by y first, so that tracing starts at the top.
+            edges.entry((from.1, from.0)).or_default().push(to);
+        };
+        for y in 0..height {
+            for x in 0..width {
+                if !is_inside(x, y) {
+                    continue;
+                }
+                if !is_inside(x, y - 1) {
+                    add_edge((x, y), (x + 1, y));
+                }
+                if !is_inside(x + 1, y) {
+                    add_edge((x + 1, y), (x + 1, y + 1));
+                }
+                if !is_inside(x, y + 1) {
+                    add_edge((x + 1, y + 1), (x, y + 1));
+                }
+                if !is_inside(x - 1, y) {
+                    add_edge((x, y + 1), (x, y));
+                }
+            }
+        }
+
+        let mut contours = Vec::new();
+        while let Some((&(start_y, start_x), _)) = edges.first_key_value() {
+            let start = (start_x, start_y);
+            let mut contour = vec![start];
+            let mut current = start;
+            let mut direction = (0, 0);
+            while let Some(ends) = edges.get_mut(&(current.1, current.0)) {
+                // Where two outlines touch at a corner, turn right, then
+                // go straight, then turn left, which keeps diagonally
+                // touching regions apart.
+                let right = (-direction.1, direction.0);
+                let index = [right, direction]
+                    .iter()
+                    .find_map(|preferred| {
+                        ends.iter()
+                            .position(|end| (end.0 - current.0, end.1 - current.1) == *preferred)
+                    })
+                    .unwrap_or(0);
+                let next = ends.remove(index);
+                if ends.is_empty() {
+                    edges.remove(&(current.1, current.0));
+                }
+                direction = (next.0 - current.0, next.1 - current.1);
+                current = next;
+                if current == start {
+                    break;
+                }
+                contour.push(current);
+            }
+            contours.push(remove_collinear_points(contour));
+        }
+        contours
+    }
+}
+
+// MARK: Hashing
+
+impl Image {
+    /// Returns a perceptual difference hash of the image. The image is
+    /// reduced to a 9 x 8 grid of average greyscale values, and each bit
+    /// records whether a cell is brighter than the cell to its right.
+    /// Transparent pixels are treated as black. Images that look alike
+    /// have hashes that differ in only a few bits.
+    pub fn dhash(&self) -> u64 {
+        let grid = self.greyscale_grid(Size {
+            width: 9,
+            height: 8,
+        });
+        let mut hash = 0;
+        for row in grid.chunks_exact(9) {
+            for pair in row.windows(2) {
+                hash = (hash << 1) | (pair[0] > pair[1]) as u64;
+            }
+        }
+        hash
+    }
+
+    /// Returns the number of bits that differ between the difference
+    /// hashes of two images. Small distances mean the images look alike.
+    pub fn hamming_distance(&self, other: &Image) -> u32 {
+        (self.dhash() ^ other.dhash()).count_ones()
+    }
+
+    /// Returns the average greyscale value of each cell when the image is
+    /// divided into a grid of the given size, in row-major order.
+    fn greyscale_grid(&self, grid_size: Size<u32>) -> Vec<f32> {
+        let cell_range = |index: u32, cells: u32, length: u32| {
+            let start = (index as u64 * length as u64 / cells as u64) as u32;
+            let end = ((index + 1) as u64 * length as u64 / cells as u64) as u32;
+            start..end.max(start + 1).min(length)
+        };
+
+        let mut grid = Vec::with_capacity((grid_size.width * grid_size.height) as usize);
+        for cell_y in 0..grid_size.height {
+            for cell_x in 0..grid_size.width {
+                let mut total = 0.0;
+                let mut count = 0;
+                for y in cell_range(cell_y, grid_size.height, self.size.height) {
+                    for x in cell_range(cell_x, grid_size.width, self.size.width) {
+                        let Some(color) = self.pixel_color(Point { x, y }.into()) else {
+                            continue;
+                        };
+                        let alpha = color.alpha as f32 / u8::MAX as f32;
+                        total += color.grayscale().red as f32 * alpha;
+                        count += 1;
+                    }
+                }
+                grid.push(if count == 0 {
+                    0.0
+                } else {
+                    total / count as f32
+                });
+            }
+        }
+        grid
+    }
+}
+
+// MARK: Helper methods
+
+/// Marks every pixel that is 4-connected to the start pixel and shares its
+/// value in `pixels` as visited. Returns the bounding box of the region.
+fn fill_region(
+    pixels: &[bool],
+    visited: &mut [bool],
+    size: Size<u32>,
+    start: Point<usize>,
+) -> Rect<i32> {
+    let width = size.width as usize;
+    let height = size.height as usize;
+    let value = pixels[start.y * width + start.x];
+
+    let mut min = start;
+    let mut max = start;
+    let mut points = vec![start];
+    visited[start.y * width + start.x] = true;
+
+    while let Some(point) = points.pop() {
+        min.x = min.x.min(point.x);
+        min.y = min.y.min(point.y);
+        max.x = max.x.max(point.x);
+        max.y = max.y.max(point.y);
+
+        let neighbours = [
+            (point.x > 0).then(|| Point {
+                x: point.x - 1,
+                y: point.y,
+            }),
+            (point.x + 1 < width).then(|| Point {
+                x: point.x + 1,
+                y: point.y,
+            }),
+            (point.y > 0).then(|| Point {
+                x: point.x,
+                y: point.y - 1,
+            }),
+            (point.y + 1 < height).then(|| Point {
+                x: point.x,
+                y: point.y + 1,
+            }),
+        ];
+        for neighbour in neighbours.into_iter().flatten() {
+            let index = neighbour.y * width + neighbour.x;
+            if !visited[index] && pixels[index] == value {
+                visited[index] = true;
+                points.push(neighbour);
+            }
+        }
+    }
+
+    Rect::new(
+        min.x as i32,
+        min.y as i32,
+        (max.x - min.x + 1) as i32,
+        (max.y - min.y + 1) as i32,
+    )
+}
+
+/// Removes the points of a closed polyline that lie on a straight line
+/// between their neighbours.
+fn remove_collinear_points(points: Vec<(i32, i32)>) -> Vec<Point<i32>> {
+    let count = points.len();
+    (0..count)
+        .filter(|&index| {
+            let previous = points[(index + count - 1) % count];
+            let point = points[index];
+            let next = points[(index + 1) % count];
+            let incoming = (point.0 - previous.0, point.1 - previous.1);
+            let outgoing = (next.0 - point.0, next.1 - point.1);
+            incoming.0 * outgoing.1 - incoming.1 * outgoing.0 != 0
+        })
+        .map(|index| Point {
+            x: points[index].0,
+            y: points[index].1,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, Image, Point, Rect, Size};
+
+    #[test]
+    fn dhash() {
+        let gerbil = Image::open("tests/images/gerbil.jpg").unwrap();
+        let rgb = ::image::DynamicImage::ImageRgba8(gerbil.to_image_buffer().unwrap()).to_rgb8();
+        let mut data = Vec::new();
+        ::image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 30)
+            .encode_image(&rgb)
+            .unwrap();
+        let recompressed = Image::from_file_data(&data).unwrap();
+        let mountain = Image::open("tests/images/mountain.png").unwrap();
+
+        assert_eq!(gerbil.hamming_distance(&gerbil), 0);
+        assert!(gerbil.hamming_distance(&recompressed) <= 4);
+        assert!(gerbil.hamming_distance(&mountain) >= 16);
+    }
+
+    #[test]
+    fn enclosed_transparent_regions() {
+        let mut image = Image::empty(Size {
+            width: 9,
+            height: 8,
+        });
+        let hole = Rect::new(3, 2, 2, 3);
+        for y in 1..7 {
+            for x in 1..7 {
+                let is_in_hole = (3..5).contains(&x) && (2..5).contains(&y);
+                if !is_in_hole {
+                    image.set_pixel_color(Color::BLACK, Point { x, y });
+                }
+            }
+        }
+
+        assert_eq!(image.enclosed_transparent_regions(), vec![hole]);
+        assert!(Image::empty(image.size)
+            .enclosed_transparent_regions()
+            .is_empty());
+    }
+
+    #[test]
+    fn opaque_convex_hull() {
+        let mut image = Image::empty(Size {
+            width: 8,
+            height: 8,
+        });
+        for y in 1..6 {
+            for x in 1..3 {
+                image.set_pixel_color(Color::BLACK, Point { x, y });
+            }
+        }
+        for y in 4..6 {
+            for x in 1..6 {
+                image.set_pixel_color(Color::BLACK, Point { x, y });
+            }
+        }
+
+        let expected_hull = vec![
+            Point { x: 1, y: 1 },
+            Point { x: 2, y: 1 },
+            Point { x: 5, y: 4 },
+            Point { x: 5, y: 5 },
+            Point { x: 1, y: 5 },
+        ];
+        assert_eq!(image.opaque_convex_hull(), expected_hull);
+        assert!(Image::empty(image.size).opaque_convex_hull().is_empty());
+    }
+
+    #[test]
+    fn alpha_contour() {
+        let mut image = Image::empty(Size {
+            width: 8,
+            height: 6,
+        });
+        for y in 1..3 {
+            for x in 2..5 {
+                image.set_pixel_color(Color::BLACK, Point { x, y });
+            }
+        }
+        let mut translucent = Color::BLACK;
+        translucent.alpha = 0x40;
+        image.set_pixel_color(translucent, Point { x: 6, y: 4 });
+
+        let expected_contour = vec![
+            Point { x: 2, y: 1 },
+            Point { x: 5, y: 1 },
+            Point { x: 5, y: 3 },
+            Point { x: 2, y: 3 },
+        ];
+        assert_eq!(image.alpha_contour(0x80), vec![expected_contour.clone()]);
+
+        let single_pixel_contour = vec![
+            Point { x: 6, y: 4 },
+            Point { x: 7, y: 4 },
+            Point { x: 7, y: 5 },
+            Point { x: 6, y: 5 },
+        ];
+        assert_eq!(
+            image.alpha_contour(0),
+            vec![expected_contour, single_pixel_contour]
+        );
+
+        let mut ring = Image::color(
+            &Color::BLACK,
+            Size {
+                width: 3,
+                height: 3,
+            },
+        );
+        ring.set_pixel_color(Color::CLEAR, Point { x: 1, y: 1 });
+        let outer_contour = vec![
+            Point { x: 0, y: 0 },
+            Point { x: 3, y: 0 },
+            Point { x: 3, y: 3 },
+            Point { x: 0, y: 3 },
+        ];
+        let hole_contour = vec![
+            Point { x: 1, y: 1 },
+            Point { x: 1, y: 2 },
+            Point { x: 2, y: 2 },
+            Point { x: 2, y: 1 },
+        ];
+        assert_eq!(ring.alpha_contour(0), vec![outer_contour, hole_contour]);
+    }
+}
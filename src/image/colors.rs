@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::{Color, Point};
 
@@ -19,11 +19,291 @@ impl Image {
         }
         colors
     }
+
+    /// Returns the mean colour of the image. The colour channels are
+    /// weighted by each pixel’s alpha, so transparent pixels contribute
+    /// nothing to them, while the alpha is a straight average.
+    /// Returns clear if the image is fully transparent.
+    pub fn average_color(&self) -> Color {
+        let mut totals = [0u64; 3];
+        let mut alpha_total = 0u64;
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact(4) {
+                let alpha = pixel[3] as u64;
+                for (total, value) in totals.iter_mut().zip(pixel) {
+                    *total += *value as u64 * alpha;
+                }
+                alpha_total += alpha;
+            }
+        }
+
+        let pixel_count = self.size.width as u64 * self.size.height as u64;
+        if alpha_total == 0 || pixel_count == 0 {
+            return Color::CLEAR;
+        }
+        let weighted_average = |total: u64| ((total + alpha_total / 2) / alpha_total) as u8;
+        Color {
+            red: weighted_average(totals[0]),
+            green: weighted_average(totals[1]),
+            blue: weighted_average(totals[2]),
+            alpha: ((alpha_total + pixel_count / 2) / pixel_count) as u8,
+        }
+    }
+
+    /// Returns each distinct colour in the image, along with the number
+    /// of pixels that use it. Colours are compared on all four channels,
+    /// so transparent pixels with different colour values are counted
+    /// separately.
+    pub fn color_counts(&self) -> HashMap<Color, u32> {
+        let mut counts = HashMap::new();
+
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                if let Some(color) = self.pixel_color(location.into()) {
+                    *counts.entry(color).or_insert(0) += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Returns an approximate set of the colours in the image, merging
+    /// colours whose channels fall into the same `tolerance` sized bins.
+    /// Each bin is represented by the mean of the colours in it, and the
+    /// colours are sorted by their RGBA value. A tolerance of 0 or 1
+    /// returns the exact colours.
+    pub fn colors_approx(&self, tolerance: u8) -> Vec<Color> {
+        let bin_size = tolerance.max(1);
+        let mut bins: HashMap<[u8; 4], ([u64; 4], u64)> = HashMap::new();
+        for (color, count) in self.color_counts() {
+            let channels: [u8; 4] = (&color).into();
+            let bin = channels.map(|value| value / bin_size);
+            let (totals, total_count) = bins.entry(bin).or_insert(([0; 4], 0));
+            for (total, value) in totals.iter_mut().zip(channels) {
+                *total += value as u64 * count as u64;
+            }
+            *total_count += count as u64;
+        }
+
+        let mut colors: Vec<Color> = bins
+            .into_values()
+            .map(|(totals, count)| {
+                let channels = totals.map(|total| ((total + count / 2) / count) as u8);
+                Color::from(channels)
+            })
+            .collect();
+        colors.sort_by_key(|color| color.as_rgba_u32());
+        colors
+    }
+
+    /// Returns whether every pixel in the image is a colour in the palette.
+    /// Fully transparent pixels are ignored.
+    pub fn uses_only(&self, palette: &[Color]) -> bool {
+        self.offending_colors(palette).is_empty()
+    }
+
+    /// Returns the colours in the image that are not in the palette,
+    /// sorted by their RGBA value. Fully transparent pixels are ignored.
+    pub fn offending_colors(&self, palette: &[Color]) -> Vec<Color> {
+        let mut colors: Vec<Color> = self
+            .colors()
+            .into_iter()
+            .filter(|color| color.alpha != 0 && !palette.contains(color))
+            .collect();
+        colors.sort_by_key(|color| color.as_rgba_u32());
+        colors
+    }
+
+    /// Returns a palette of at most `max_colors` colours that best
+    /// represents the image, using the median cut algorithm.
+    pub fn quantize(&self, max_colors: usize) -> Vec<Color> {
+        let mut counts: HashMap<[u8; 4], u32> = HashMap::new();
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                if let Some(color) = self.pixel_color(location.into()) {
+                    *counts.entry(color.into()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        if max_colors == 0 || counts.is_empty() {
+            return Vec::new();
+        }
+
+        let mut entries: Vec<([u8; 4], u32)> = counts.into_iter().collect();
+        // Sort so that the output doesn’t depend on the hash map ordering.
+        entries.sort();
+
+        let mut boxes = vec![entries];
+        while boxes.len() < max_colors {
+            // Split the box with the widest channel range.
+            let Some((index, channel, range)) = boxes
+                .iter()
+                .enumerate()
+                .map(|(index, entries)| {
+                    let (channel, range) = widest_channel(entries);
+                    (index, channel, range)
+                })
+                .max_by_key(|(_, _, range)| *range)
+            else {
+                break;
+            };
+
+            if range == 0 {
+                break;
+            }
+
+            let mut entries = boxes.swap_remove(index);
+            entries.sort_by_key(|(color, _)| color[channel]);
+
+            let total: u32 = entries.iter().map(|(_, count)| count).sum();
+            let mut running_total = 0;
+            let mut split_index = entries.len() / 2;
+            for (index, (_, count)) in entries.iter().enumerate() {
+                running_total += count;
+                if running_total * 2 >= total {
+                    split_index = index + 1;
+                    break;
+                }
+            }
+            let split_index = split_index.clamp(1, entries.len() - 1);
+
+            let upper = entries.split_off(split_index);
+            boxes.push(entries);
+            boxes.push(upper);
+        }
+
+        boxes.iter().map(|entries| average_color(entries)).collect()
+    }
+
+    /// Maps every pixel in the image to the closest colour in the palette.
+    /// Closeness is measured as the squared distance across all four channels.
+    pub fn apply_palette(&mut self, palette: &[Color]) {
+        if palette.is_empty() {
+            return;
+        }
+
+        let mut cache: HashMap<[u8; 4], [u8; 4]> = HashMap::new();
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            for x in 0..self.size.width as usize {
+                let offset = row_start + x * 4;
+                let pixel: [u8; 4] = self.data[offset..offset + 4].try_into().unwrap();
+                let replacement = *cache
+                    .entry(pixel)
+                    .or_insert_with(|| Color::from(pixel).nearest_in(palette).unwrap().into());
+                self.data[offset..offset + 4].copy_from_slice(&replacement);
+            }
+        }
+    }
+
+    /// Rewrites every pixel in the image to its nearest colour in the
+    /// palette, as found by `Color::nearest_in`. The image is left
+    /// unchanged if the palette is empty.
+    pub fn quantize_to_palette(&mut self, palette: &[Color]) {
+        self.apply_palette(palette);
+    }
+
+    /// Constrains the image to the colours in the palette. When `dither` is
+    /// set, the quantization error is spread to neighbouring pixels using
+    /// Floyd–Steinberg dithering, otherwise each pixel is mapped to its
+    /// closest palette colour.
+    pub fn conform_to_palette(&mut self, palette: &[Color], dither: bool) {
+        if !dither {
+            self.apply_palette(palette);
+            return;
+        }
+        if palette.is_empty() {
+            return;
+        }
+
+        let width = self.size.width as usize;
+        let height = self.size.height as usize;
+        let mut errors = vec![[0.0f32; 4]; width * height];
+
+        for y in 0..height {
+            for x in 0..width {
+                let offset = y * self.bytes_per_row as usize + x * 4;
+                let error = errors[y * width + x];
+                let mut pixel = [0u8; 4];
+                let mut target = [0.0f32; 4];
+                for channel in 0..4 {
+                    target[channel] = self.data[offset + channel] as f32 + error[channel];
+                    pixel[channel] = target[channel].round().clamp(0.0, 255.0) as u8;
+                }
+
+                let replacement: [u8; 4] = Color::from(pixel).nearest_in(palette).unwrap().into();
+                self.data[offset..offset + 4].copy_from_slice(&replacement);
+
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    let ny = y + dy;
+                    if nx < 0 || nx as usize >= width || ny >= height {
+                        return;
+                    }
+                    let neighbour = &mut errors[ny * width + nx as usize];
+                    for channel in 0..4 {
+                        let difference = target[channel] - replacement[channel] as f32;
+                        neighbour[channel] += difference * weight;
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+
+    /// Returns a copy of the image mapped to a quantized palette of at most
+    /// `max_colors` colours. The image itself is left untouched.
+    pub fn quantized_preview(&self, max_colors: usize) -> Image {
+        let palette = self.quantize(max_colors);
+        let mut preview = self.clone();
+        preview.apply_palette(&palette);
+        preview
+    }
+}
+
+// MARK: Helper methods
+
+/// Returns the channel with the widest range of values, along with that range.
+fn widest_channel(entries: &[([u8; 4], u32)]) -> (usize, u8) {
+    (0..4)
+        .map(|channel| {
+            let min = entries.iter().map(|(color, _)| color[channel]).min();
+            let max = entries.iter().map(|(color, _)| color[channel]).max();
+            match (min, max) {
+                (Some(min), Some(max)) => (channel, max - min),
+                _ => (channel, 0),
+            }
+        })
+        .max_by_key(|(channel, range)| (*range, std::cmp::Reverse(*channel)))
+        .unwrap_or((0, 0))
+}
+
+/// Returns the average of the colours, weighted by their pixel counts.
+fn average_color(entries: &[([u8; 4], u32)]) -> Color {
+    let mut sums = [0u64; 4];
+    let mut total = 0u64;
+    for (color, count) in entries {
+        for channel in 0..4 {
+            sums[channel] += color[channel] as u64 * *count as u64;
+        }
+        total += *count as u64;
+    }
+    let total = total.max(1);
+    let average = sums.map(|sum| ((sum + total / 2) / total) as u8);
+    average.into()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Color, Image};
+    use crate::{Color, Image, Point, Size};
 
     #[test]
     fn colors_in_avatar() {
@@ -41,6 +321,29 @@ mod tests {
         assert!(colors.contains(&Color::from_rgb_u32(0x733e39)));
     }
 
+    #[test]
+    fn color_counts() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+        let counts = image.color_counts();
+
+        assert_eq!(counts.len(), image.colors().len());
+        assert_eq!(
+            counts.values().sum::<u32>(),
+            image.size.width * image.size.height
+        );
+
+        let mut image = Image::empty(Size {
+            width: 3,
+            height: 1,
+        });
+        let transparent_red = Color::RED.with_alpha(0);
+        image.set_pixel_color(transparent_red.clone(), Point { x: 1, y: 0 });
+        let counts = image.color_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&Color::CLEAR], 2);
+        assert_eq!(counts[&transparent_red], 1);
+    }
+
     #[test]
     #[ignore]
     fn colors_in_gerbil() {
@@ -49,4 +352,101 @@ mod tests {
 
         assert_eq!(colors.len(), 37048);
     }
+
+    #[test]
+    fn colors_approx_in_gerbil() {
+        let image = Image::open("tests/images/gerbil.jpg").unwrap();
+        let exact_count = image.colors().len();
+        let colors = image.colors_approx(16);
+
+        assert!(!colors.is_empty());
+        assert!(colors.len() * 10 < exact_count);
+        assert_eq!(image.colors_approx(0).len(), exact_count);
+    }
+
+    #[test]
+    fn offending_colors() {
+        let palette = [Color::BLACK, Color::WHITE, Color::RED];
+        let mut image = Image::color(
+            &Color::BLACK,
+            Size {
+                width: 3,
+                height: 2,
+            },
+        );
+        image.set_pixel_color(Color::WHITE, Point { x: 1, y: 0 });
+        image.set_pixel_color(Color::RED, Point { x: 2, y: 0 });
+        image.set_pixel_color(Color::from_rgba_u32(0x12345600), Point { x: 0, y: 1 });
+        assert!(image.uses_only(&palette));
+        assert!(image.offending_colors(&palette).is_empty());
+
+        let off_palette = Color::from_rgb_u32(0xfe0000);
+        image.set_pixel_color(off_palette.clone(), Point { x: 2, y: 1 });
+        assert!(!image.uses_only(&palette));
+        assert_eq!(image.offending_colors(&palette), vec![off_palette]);
+    }
+
+    #[test]
+    fn quantized_preview() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+        let original = image.clone();
+
+        let preview = image.quantized_preview(3);
+
+        assert_eq!(image, original);
+        assert_eq!(preview.size, image.size);
+        assert!(preview.colors().len() <= 3);
+    }
+
+    #[test]
+    fn conform_to_palette() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+        let palette = [
+            Color::BLACK,
+            Color::WHITE,
+            Color::from_rgb_u32(0xb86f50),
+            Color::from_rgb_u32(0x5fcde4),
+        ];
+
+        for dither in [false, true] {
+            let mut result = image.clone();
+            result.conform_to_palette(&palette, dither);
+
+            assert!(result.colors().iter().all(|color| palette.contains(color)));
+        }
+    }
+
+    #[test]
+    fn quantize_to_palette() {
+        let palette = [Color::RED, Color::GREEN, Color::BLUE, Color::WHITE];
+        let mut image = Image::color(
+            &Color::from_rgb_u32(0xd02010),
+            Size {
+                width: 2,
+                height: 2,
+            },
+        );
+        image.set_pixel_color(Color::from_rgb_u32(0xe0e0f0), Point { x: 1, y: 1 });
+
+        image.quantize_to_palette(&palette);
+
+        assert_eq!(image.pixel_color(Point { x: 0, y: 0 }), Some(Color::RED));
+        assert_eq!(image.pixel_color(Point { x: 1, y: 0 }), Some(Color::RED));
+        assert_eq!(image.pixel_color(Point { x: 1, y: 1 }), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn average_color() {
+        let mut image = Image::empty(Size {
+            width: 2,
+            height: 1,
+        });
+        image.set_pixel_color(Color::RED, Point { x: 0, y: 0 });
+        assert_eq!(image.average_color(), Color::RED.with_alpha(128));
+
+        image.set_pixel_color(Color::BLUE.with_alpha(0x33), Point { x: 1, y: 0 });
+        assert_eq!(image.average_color(), Color::from_rgba_u32(0xd5002b99));
+
+        assert_eq!(Image::empty(image.size).average_color(), Color::CLEAR);
+    }
 }
@@ -0,0 +1,88 @@
+use crate::{
+    composite::{self, Layer},
+    Color, Point, Size,
+};
+
+use super::Image;
+
+/// Arranges images into a grid with `columns` columns, for reviewing them
+/// side by side. Every cell is the size of the largest image, with
+/// `padding` pixels between the cells, and each image is centred in its
+/// cell and drawn over the background colour. Images are placed from left
+/// to right, then top to bottom.
+pub fn contact_sheet(images: &[Image], columns: u32, padding: u32, background: &Color) -> Image {
+    let columns = columns.max(1).min(images.len().max(1) as u32);
+    let rows = (images.len() as u32).div_ceil(columns);
+    let cell_size = Size {
+        width: images
+            .iter()
+            .map(|image| image.size.width)
+            .max()
+            .unwrap_or(0),
+        height: images
+            .iter()
+            .map(|image| image.size.height)
+            .max()
+            .unwrap_or(0),
+    };
+    let sheet_size = Size {
+        width: columns * cell_size.width + columns.saturating_sub(1) * padding,
+        height: rows * cell_size.height + rows.saturating_sub(1) * padding,
+    };
+
+    let mut sheet = Image::color(background, sheet_size);
+    for (index, image) in images.iter().enumerate() {
+        let column = index as u32 % columns;
+        let row = index as u32 / columns;
+        let position = Point {
+            x: column * (cell_size.width + padding) + (cell_size.width - image.size.width) / 2,
+            y: row * (cell_size.height + padding) + (cell_size.height - image.size.height) / 2,
+        };
+        let layer = Layer::new(image, position.into());
+        composite::draw_layer_over_image(&mut sheet, &layer);
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, Image, Point, Rect, Size};
+
+    use super::contact_sheet;
+
+    #[test]
+    fn two_columns() {
+        let square = Size {
+            width: 4,
+            height: 4,
+        };
+        let images = [
+            Image::color(&Color::RED, square),
+            Image::color(&Color::GREEN, square),
+            Image::color(
+                &Color::BLUE,
+                Size {
+                    width: 2,
+                    height: 2,
+                },
+            ),
+        ];
+
+        let sheet = contact_sheet(&images, 2, 1, &Color::WHITE);
+
+        assert_eq!(
+            sheet.size,
+            Size {
+                width: 9,
+                height: 9,
+            }
+        );
+        assert_eq!(sheet.subimage(Rect::new(5, 0, 4, 4)).unwrap(), images[1]);
+        // The smaller image is centred in its cell.
+        assert_eq!(sheet.subimage(Rect::new(1, 6, 2, 2)).unwrap(), images[2]);
+        assert_eq!(sheet.pixel_color(Point { x: 0, y: 5 }), Some(Color::WHITE));
+        // The padding and the empty cell show the background.
+        assert_eq!(sheet.pixel_color(Point { x: 4, y: 0 }), Some(Color::WHITE));
+        assert_eq!(sheet.pixel_color(Point { x: 7, y: 7 }), Some(Color::WHITE));
+    }
+}
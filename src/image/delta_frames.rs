@@ -0,0 +1,217 @@
+use crate::{Point, Rect, Size};
+
+use super::Image;
+
+/// The bytes that identify delta encoded frames.
+const MAGIC: &[u8; 4] = b"GDLT";
+
+/// Encodes a sequence of frames that share the same dimensions. The first
+/// frame is stored in full and each subsequent frame only stores the region
+/// that differs from the previous frame.
+///
+/// The data starts with four magic bytes followed by the frame count, width
+/// and height as little endian `u32`s, then the pixel data of the first
+/// frame. Each following frame is stored as the x, y, width and height of
+/// the changed region, as little endian `u32`s, followed by the pixel data
+/// of that region.
+pub fn encode_delta_frames(frames: &[Image]) -> anyhow::Result<Vec<u8>> {
+    let Some(first_frame) = frames.first() else {
+        anyhow::bail!("There are no frames to encode.");
+    };
+    let size = first_frame.size;
+    if frames.iter().any(|frame| frame.size != size) {
+        anyhow::bail!("All frames must share the same dimensions.");
+    }
+
+    let mut output = Vec::new();
+    output.extend_from_slice(MAGIC);
+    output.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+    output.extend_from_slice(&size.width.to_le_bytes());
+    output.extend_from_slice(&size.height.to_le_bytes());
+    append_pixels(&mut output, first_frame);
+
+    for pair in frames.windows(2) {
+        let region = pair[0].diff_region(&pair[1])?.unwrap_or(Rect::zero());
+        for value in [
+            region.origin.x,
+            region.origin.y,
+            region.size.width,
+            region.size.height,
+        ] {
+            output.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        if region.size.width > 0 && region.size.height > 0 {
+            let changes = pair[1].subimage(region)?;
+            append_pixels(&mut output, &changes);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Decodes frames produced by `encode_delta_frames`.
+pub fn decode_delta_frames(data: &[u8]) -> anyhow::Result<Vec<Image>> {
+    let mut reader = Reader { data, position: 0 };
+    if reader.read_bytes(4)? != MAGIC {
+        anyhow::bail!("The data is not delta encoded frame data.");
+    }
+    let frame_count = reader.read_u32()? as usize;
+    let size = Size {
+        width: reader.read_u32()?,
+        height: reader.read_u32()?,
+    };
+    if frame_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut frame = reader.read_image(size)?;
+    // The frame count comes from the data, so the capacity is limited by
+    // the number of region headers that could actually follow.
+    let mut frames = Vec::with_capacity(frame_count.min(reader.remaining() / 16 + 1));
+    frames.push(frame.clone());
+
+    for _ in 1..frame_count {
+        let origin = Point {
+            x: reader.read_u32()?,
+            y: reader.read_u32()?,
+        };
+        let region_size = Size {
+            width: reader.read_u32()?,
+            height: reader.read_u32()?,
+        };
+        if region_size.width > 0 && region_size.height > 0 {
+            let is_within_frame = |start: u32, length: u32, frame_length: u32| {
+                start
+                    .checked_add(length)
+                    .is_some_and(|end| end <= frame_length)
+            };
+            if !is_within_frame(origin.x, region_size.width, size.width)
+                || !is_within_frame(origin.y, region_size.height, size.height)
+            {
+                anyhow::bail!("A changed region is outside of the frame bounds.");
+            }
+            let changes = reader.read_image(region_size)?;
+            frame.draw_image_over(&changes, origin.into());
+        }
+        frames.push(frame.clone());
+    }
+
+    Ok(frames)
+}
+
+// MARK: Helper methods
+
+/// Appends the visible pixel data of an image, without any row padding.
+fn append_pixels(output: &mut Vec<u8>, image: &Image) {
+    let row_length = image.size.width as usize * 4;
+    for y in 0..image.size.height as usize {
+        let row_start = y * image.bytes_per_row as usize;
+        output.extend_from_slice(&image.data[row_start..row_start + row_length]);
+    }
+}
+
+/// Reads values sequentially from encoded data.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    /// Returns the number of bytes left to read.
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    /// Reads a number of bytes.
+    fn read_bytes(&mut self, count: usize) -> anyhow::Result<&[u8]> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(anyhow::anyhow!("The delta encoded data is truncated."))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Reads a little endian `u32`.
+    fn read_u32(&mut self) -> anyhow::Result<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into()?))
+    }
+
+    /// Reads the tightly packed pixel data for an image of the given size.
+    fn read_image(&mut self, size: Size<u32>) -> anyhow::Result<Image> {
+        let too_large = || anyhow::anyhow!("The delta encoded frame size is too large.");
+        let bytes_per_row = size.width.checked_mul(4).ok_or_else(too_large)?;
+        let length = (bytes_per_row as usize)
+            .checked_mul(size.height as usize)
+            .ok_or_else(too_large)?;
+        let data = self.read_bytes(length)?.to_vec();
+        Ok(Image::new(data, size, bytes_per_row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, Image, Point};
+
+    #[test]
+    fn delta_frames_round_trip() {
+        let first_frame = Image::open("tests/images/avatar.png").unwrap();
+        let mut second_frame = first_frame.clone();
+        second_frame.set_pixel_color(Color::RED, Point { x: 3, y: 4 });
+        second_frame.set_pixel_color(Color::BLUE, Point { x: 5, y: 7 });
+        let mut third_frame = second_frame.clone();
+        third_frame.set_pixel_color(Color::GREEN, Point { x: 12, y: 15 });
+        let frames = vec![first_frame.clone(), second_frame, third_frame];
+        let unchanged_frames = vec![first_frame.clone(), first_frame];
+
+        for frames in [frames, unchanged_frames] {
+            let data = super::encode_delta_frames(&frames).unwrap();
+            let raw_length: usize = frames.iter().map(|frame| frame.data.len()).sum();
+            assert!(data.len() < raw_length);
+
+            let result = super::decode_delta_frames(&data).unwrap();
+            assert_eq!(result, frames);
+        }
+    }
+
+    #[test]
+    fn delta_frames_malformed_data() {
+        let header = |frame_count: u32, width: u32, height: u32| {
+            let mut data = super::MAGIC.to_vec();
+            for value in [frame_count, width, height] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data
+        };
+
+        // A huge frame count with no frames to back it up.
+        let mut data = header(u32::MAX, 1, 1);
+        data.extend_from_slice(&[0; 4]);
+        assert!(super::decode_delta_frames(&data).is_err());
+
+        // A frame size that overflows.
+        let data = header(1, u32::MAX, u32::MAX);
+        assert!(super::decode_delta_frames(&data).is_err());
+
+        // A changed region whose end overflows.
+        let mut data = header(2, 2, 2);
+        data.extend_from_slice(&[0; 16]);
+        for value in [u32::MAX, 0, 1, 1] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0; 4]);
+        assert!(super::decode_delta_frames(&data).is_err());
+
+        // A changed region that is truncated.
+        let mut data = header(2, 2, 2);
+        data.extend_from_slice(&[0; 16]);
+        for value in [0u32, 0, 2, 2] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0; 4]);
+        assert!(super::decode_delta_frames(&data).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{
+    composite::{self, Layer},
+    BlendMode, Color, Point,
+};
+
+use super::Image;
+
+/// The width of each glyph in the bitmap font.
+const GLYPH_WIDTH: i32 = 5;
+/// The height of each glyph in the bitmap font.
+const GLYPH_HEIGHT: i32 = 7;
+/// The horizontal distance between the start of one glyph and the next.
+const GLYPH_ADVANCE: i32 = GLYPH_WIDTH + 1;
+/// The vertical distance between the start of one line and the next.
+const LINE_ADVANCE: i32 = GLYPH_HEIGHT + 1;
+
+/// A 5x7 bitmap font covering the printable ASCII characters, starting at
+/// the space character. Each glyph is stored as five columns, from left to
+/// right, where the least significant bit is the top row.
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // #
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // )
+    [0x08, 0x2a, 0x1c, 0x2a, 0x08], // *
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // 0
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // @
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // A
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // D
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7f, 0x09, 0x09, 0x01, 0x01], // F
+    [0x3e, 0x41, 0x41, 0x51, 0x32], // G
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // H
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // J
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7f, 0x02, 0x04, 0x02, 0x7f], // M
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // N
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // O
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // Q
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // T
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // U
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // V
+    [0x7f, 0x20, 0x18, 0x20, 0x7f], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x03, 0x04, 0x78, 0x04, 0x03], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // f
+    [0x08, 0x14, 0x54, 0x54, 0x3c], // g
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // j
+    [0x00, 0x7f, 0x10, 0x28, 0x44], // k
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // l
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // q
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // t
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // u
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // v
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // y
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+impl Image {
+    /// Draws text into the image using a built-in 5x7 bitmap font.
+    /// The position is the top left of the first character, and each
+    /// font pixel is drawn as a `scale` x `scale` square of solid colour.
+    /// Newlines start a new line and characters outside of the printable
+    /// ASCII range are drawn as question marks.
+    pub fn draw_text(&mut self, text: &str, position: Point<i32>, color: &Color, scale: u32) {
+        let scale = scale as i32;
+        let mut origin = position;
+
+        for character in text.chars() {
+            if character == '\n' {
+                origin.x = position.x;
+                origin.y += LINE_ADVANCE * scale;
+                continue;
+            }
+
+            let glyph = glyph(character);
+            for (column, bits) in glyph.iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if bits & (1 << row) == 0 {
+                        continue;
+                    }
+                    let x = origin.x + column as i32 * scale;
+                    let y = origin.y + row * scale;
+                    self.fill_square(Point { x, y }, scale, color);
+                }
+            }
+
+            origin.x += GLYPH_ADVANCE * scale;
+        }
+    }
+
+    /// Fills a square of pixels, ignoring any that fall outside of the image.
+    fn fill_square(&mut self, origin: Point<i32>, length: i32, color: &Color) {
+        for y in origin.y..origin.y + length {
+            for x in origin.x..origin.x + length {
+                if x < 0 || y < 0 || x >= self.size.width as i32 || y >= self.size.height as i32 {
+                    continue;
+                }
+                self.set_pixel_color(color.clone(), Point { x, y }.into());
+            }
+        }
+    }
+}
+
+// MARK: Stamping
+
+impl Image {
+    /// Stamps a brush image repeatedly along a path, as a brush engine
+    /// would. The brush is centred on points `spacing` pixels apart along
+    /// the polyline, starting at the first point, and each stamp is
+    /// blended with the given blend mode and opacity. Spacing below one
+    /// pixel is treated as one pixel.
+    pub fn stamp_along(
+        &mut self,
+        brush: &Image,
+        path: &[Point<f32>],
+        spacing: f32,
+        blend_mode: BlendMode,
+        opacity: f32,
+    ) {
+        let Some(&start) = path.first() else {
+            return;
+        };
+        let spacing = spacing.max(1.0);
+
+        let mut stamp_points = vec![start];
+        // The distance along the path to the next stamp.
+        let mut next_distance = spacing;
+        let mut travelled = 0.0;
+        for segment in path.windows(2) {
+            let length = segment[0].distance(segment[1]);
+            while next_distance <= travelled + length {
+                let t = if length > 0.0 {
+                    (next_distance - travelled) / length
+                } else {
+                    0.0
+                };
+                stamp_points.push(segment[0].lerp(segment[1], t));
+                next_distance += spacing;
+            }
+            travelled += length;
+        }
+
+        let half_size = Point {
+            x: brush.size.width as f32 * 0.5,
+            y: brush.size.height as f32 * 0.5,
+        };
+        for point in stamp_points {
+            let mut layer = Layer::new(brush, point - half_size);
+            layer.blend_mode = blend_mode;
+            layer.opacity = opacity;
+            composite::draw_layer_over_image(self, &layer);
+        }
+    }
+}
+
+/// Returns the glyph for a character.
+fn glyph(character: char) -> &'static [u8; 5] {
+    let index = character as usize;
+    if (0x20..0x7f).contains(&index) {
+        &FONT[index - 0x20]
+    } else {
+        &FONT['?' as usize - 0x20]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BlendMode, Color, Image, Point, Size};
+
+    #[test]
+    fn draw_text() {
+        let mut image = Image::empty(Size {
+            width: 12,
+            height: 16,
+        });
+
+        image.draw_text("A", Point { x: 1, y: 1 }, &Color::RED, 2);
+
+        let pixel = |x: i32, y: i32| image.pixel_color(Point { x, y }).unwrap();
+
+        // The top of the left leg is empty, but the pixel below is lit.
+        assert_eq!(pixel(1, 1), Color::CLEAR);
+        assert_eq!(pixel(1, 3), Color::RED);
+        assert_eq!(pixel(2, 4), Color::RED);
+        // The top of the second column is lit.
+        assert_eq!(pixel(3, 1), Color::RED);
+        // The crossbar.
+        assert_eq!(pixel(5, 9), Color::RED);
+        // The middle of the counter is empty.
+        assert_eq!(pixel(5, 5), Color::CLEAR);
+        // Nothing is drawn outside of the glyph.
+        assert_eq!(pixel(0, 0), Color::CLEAR);
+        assert_eq!(pixel(11, 15), Color::CLEAR);
+    }
+
+    #[test]
+    fn stamp_along() {
+        let mut image = Image::empty(Size {
+            width: 20,
+            height: 5,
+        });
+        let brush = Image::color(
+            &Color::RED,
+            Size {
+                width: 3,
+                height: 3,
+            },
+        );
+        let path = [Point { x: 2.5, y: 2.5 }, Point { x: 17.5, y: 2.5 }];
+
+        image.stamp_along(&brush, &path, 5.0, BlendMode::Normal, 1.0);
+
+        for x in 0..20 {
+            let expected = if x % 5 == 4 || x % 5 == 0 {
+                Color::CLEAR
+            } else {
+                Color::RED
+            };
+            for y in 1..4 {
+                assert_eq!(image.pixel_color(Point { x, y }), Some(expected.clone()));
+            }
+            assert_eq!(image.pixel_color(Point { x, y: 0 }), Some(Color::CLEAR));
+            assert_eq!(image.pixel_color(Point { x, y: 4 }), Some(Color::CLEAR));
+        }
+    }
+}
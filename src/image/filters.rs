@@ -0,0 +1,1359 @@
+use crate::{
+    composite::{self, Layer},
+    BlendMode, Color, Point, Size,
+};
+
+use super::Image;
+
+impl Image {
+    /// Returns a greyscale image where each pixel encodes the perceptual
+    /// luma of the corresponding pixel, using the Rec. 709 weights.
+    /// The alpha channel is preserved.
+    pub fn luma_map(&self) -> Image {
+        let mut result = Image::empty(self.size);
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                let Some(color) = self.pixel_color(location.into()) else {
+                    continue;
+                };
+                result.set_pixel_color(color.grayscale(), location);
+            }
+        }
+        result
+    }
+
+    /// Converts every pixel to greyscale using its Rec. 709 luma, as
+    /// described by `Color::grayscale`. The alpha channel is preserved.
+    pub fn grayscale(&mut self) {
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                let Some(color) = self.pixel_color(location.into()) else {
+                    continue;
+                };
+                self.set_pixel_color(color.grayscale(), location);
+            }
+        }
+    }
+
+    /// Sets every pixel to black if its Rec. 709 luma is below `level`,
+    /// or white otherwise. The alpha channel is preserved.
+    pub fn threshold(&mut self, level: u8) {
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                let Some(color) = self.pixel_color(location.into()) else {
+                    continue;
+                };
+                let new_color = if color.grayscale().red < level {
+                    Color::BLACK
+                } else {
+                    Color::WHITE
+                };
+                self.set_pixel_color(new_color.with_alpha(color.alpha), location);
+            }
+        }
+    }
+
+    /// Reduces each colour channel to `levels` evenly spaced values,
+    /// giving flat bands of colour. Fewer than two levels are treated
+    /// as two. The alpha channel is preserved.
+    pub fn posterize(&mut self, levels: u8) {
+        let levels = levels.max(2) as u32;
+        let posterize = |value: u8| {
+            let band = (value as u32 * levels / 256).min(levels - 1);
+            ((band * 255) as f32 / (levels - 1) as f32).round() as u8
+        };
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                let Some(color) = self.pixel_color(location.into()) else {
+                    continue;
+                };
+                let new_color = Color {
+                    red: posterize(color.red),
+                    green: posterize(color.green),
+                    blue: posterize(color.blue),
+                    alpha: color.alpha,
+                };
+                self.set_pixel_color(new_color, location);
+            }
+        }
+    }
+
+    /// Snaps the alpha of every pixel to the nearest of `levels` evenly
+    /// spaced values from 0 to 255, which cleans up soft edges before
+    /// exporting to a format with limited alpha. Fewer than two levels
+    /// are treated as two. The colour channels are preserved.
+    pub fn quantize_alpha(&mut self, levels: u8) {
+        let steps = (levels.max(2) - 1) as f32;
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact_mut(4) {
+                let step = (pixel[3] as f32 / 255.0 * steps).round();
+                pixel[3] = (step * 255.0 / steps).round() as u8;
+            }
+        }
+    }
+
+    /// Replaces each colour channel with its inverse, giving a negative of
+    /// the image. When `invert_alpha` is set, the alpha channel is inverted
+    /// too, otherwise it is preserved.
+    pub fn invert_colors(&mut self, invert_alpha: bool) {
+        let channel_count = if invert_alpha { 4 } else { 3 };
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact_mut(4) {
+                for value in &mut pixel[..channel_count] {
+                    *value = u8::MAX - *value;
+                }
+            }
+        }
+    }
+
+    /// Adjusts the brightness and contrast of every pixel. Each colour
+    /// channel is scaled about mid-grey by `contrast`, then offset by
+    /// `brightness`, which is expected to be between -1 and 1. The alpha
+    /// channel is preserved.
+    pub fn adjust_brightness_contrast(&mut self, brightness: f32, contrast: f32) {
+        let adjust = |value: u8| {
+            let value = value as f32 / 255.0;
+            let value = (value - 0.5) * contrast + 0.5 + brightness;
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact_mut(4) {
+                for value in &mut pixel[..3] {
+                    *value = adjust(*value);
+                }
+            }
+        }
+    }
+
+    /// Rotates the hue of every pixel by a number of degrees, except for
+    /// pixels within `protect_tolerance` of the protected colour, which
+    /// are left untouched.
+    pub fn shift_hue_except(&mut self, degrees: f32, protect: &Color, protect_tolerance: u8) {
+        let shift = degrees / 360.0;
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                let Some(mut color) = self.pixel_color(location.into()) else {
+                    continue;
+                };
+                if color.is_within_tolerance_of(protect, protect_tolerance) {
+                    continue;
+                }
+                let hue = (color.hue() + shift).rem_euclid(1.0);
+                color.set_hue(hue);
+                self.set_pixel_color(color, location);
+            }
+        }
+    }
+}
+
+// MARK: Pixel mapping
+
+impl Image {
+    /// Replaces every pixel with the colour returned by `f` for it.
+    pub fn map_pixels<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Color) -> Color,
+    {
+        let row_length = self.size.width as usize * 4;
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            for pixel in self.data[row_start..row_start + row_length].chunks_exact_mut(4) {
+                map_pixel(pixel, &mut f);
+            }
+        }
+    }
+
+    /// Replaces every pixel with the colour returned by `f` for it, like
+    /// `map_pixels`, but processes the rows on multiple threads. This is
+    /// worthwhile for per-pixel transforms of large images.
+    #[cfg(feature = "rayon")]
+    pub fn map_pixels_parallel<F>(&mut self, f: F)
+    where
+        F: Fn(Color) -> Color + Sync,
+    {
+        use rayon::prelude::*;
+
+        let bytes_per_row = self.bytes_per_row as usize;
+        let row_length = self.size.width as usize * 4;
+        if bytes_per_row == 0 {
+            return;
+        }
+        self.data
+            .par_chunks_mut(bytes_per_row)
+            .take(self.size.height as usize)
+            .for_each(|row| {
+                for pixel in row[..row_length].chunks_exact_mut(4) {
+                    map_pixel(pixel, &f);
+                }
+            });
+    }
+}
+
+// MARK: Curves
+
+impl Image {
+    /// Remaps each colour channel through a 256 entry lookup table, where
+    /// the entry at a channel’s value is its new value. The alpha channel
+    /// is preserved. Use `curve_from_points` to build a table from the
+    /// control points of a curves tool.
+    pub fn apply_curves(&mut self, red: &[u8; 256], green: &[u8; 256], blue: &[u8; 256]) {
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact_mut(4) {
+                pixel[0] = red[pixel[0] as usize];
+                pixel[1] = green[pixel[1] as usize];
+                pixel[2] = blue[pixel[2] as usize];
+            }
+        }
+    }
+}
+
+/// Builds a 256 entry lookup table for `Image::apply_curves` that passes
+/// through the control points, which are pairs of input and output values.
+/// The points are joined with monotone cubic interpolation, so the curve
+/// is smooth but never overshoots between points. Values beyond the first
+/// and last points are held level, and the identity is returned if there
+/// are no points. Where points share an input, the last one is used.
+pub fn curve_from_points(points: &[(u8, u8)]) -> [u8; 256] {
+    let mut points = points.to_vec();
+    points.reverse();
+    points.sort_by_key(|(x, _)| *x);
+    points.dedup_by_key(|(x, _)| *x);
+    if points.is_empty() {
+        return std::array::from_fn(|index| index as u8);
+    }
+
+    let xs: Vec<f32> = points.iter().map(|(x, _)| *x as f32).collect();
+    let ys: Vec<f32> = points.iter().map(|(_, y)| *y as f32).collect();
+    let count = points.len();
+
+    // Fritsch–Carlson tangents.
+    let secants: Vec<f32> = (0..count - 1)
+        .map(|index| (ys[index + 1] - ys[index]) / (xs[index + 1] - xs[index]))
+        .collect();
+    let mut tangents = vec![0.0; count];
+    if count > 1 {
+        tangents[0] = secants[0];
+        tangents[count - 1] = secants[count - 2];
+        for index in 1..count - 1 {
+            let (before, after) = (secants[index - 1], secants[index]);
+            if before * after > 0.0 {
+                tangents[index] = (before + after) / 2.0;
+            }
+        }
+        for (index, secant) in secants.iter().enumerate() {
+            if *secant == 0.0 {
+                tangents[index] = 0.0;
+                tangents[index + 1] = 0.0;
+                continue;
+            }
+            let a = tangents[index] / secant;
+            let b = tangents[index + 1] / secant;
+            let magnitude = a * a + b * b;
+            if magnitude > 9.0 {
+                let scale = 3.0 / magnitude.sqrt();
+                tangents[index] = scale * a * secant;
+                tangents[index + 1] = scale * b * secant;
+            }
+        }
+    }
+
+    std::array::from_fn(|index| {
+        let x = index as f32;
+        let segment = xs.partition_point(|point_x| *point_x <= x);
+        let value = if segment == 0 {
+            ys[0]
+        } else if segment == count {
+            ys[count - 1]
+        } else {
+            let start = segment - 1;
+            let width = xs[segment] - xs[start];
+            let t = (x - xs[start]) / width;
+            let (t2, t3) = (t * t, t * t * t);
+            (2.0 * t3 - 3.0 * t2 + 1.0) * ys[start]
+                + (t3 - 2.0 * t2 + t) * width * tangents[start]
+                + (-2.0 * t3 + 3.0 * t2) * ys[segment]
+                + (t3 - t2) * width * tangents[segment]
+        };
+        value.round().clamp(0.0, 255.0) as u8
+    })
+}
+
+// MARK: Colour overlay
+
+impl Image {
+    /// Blends a solid colour over every pixel in the image with the given
+    /// blend mode and opacity. This gives the same result as drawing a
+    /// layer filled with the colour over the whole image.
+    pub fn apply_color(&mut self, color: &Color, blend_mode: BlendMode, opacity: f32) {
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            for x in 0..self.size.width as usize {
+                let offset = row_start + x * 4;
+                let pixel: [u8; 4] = self.data[offset..offset + 4].try_into().unwrap();
+                let mut pixel_color = Color::from(pixel);
+                composite::blend_color_over(&mut pixel_color, color, blend_mode, opacity);
+                let pixel: [u8; 4] = pixel_color.into();
+                self.data[offset..offset + 4].copy_from_slice(&pixel);
+            }
+        }
+    }
+
+    /// Composites every pixel over a solid background colour, as the
+    /// compositor would draw the image over a layer filled with it. This
+    /// flattens the transparency for formats without an alpha channel,
+    /// and the result is fully opaque as long as the background is.
+    pub fn flatten_onto(&mut self, background: &Color) {
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            for x in 0..self.size.width as usize {
+                let offset = row_start + x * 4;
+                let pixel: [u8; 4] = self.data[offset..offset + 4].try_into().unwrap();
+                let mut flattened = background.clone();
+                composite::blend_color_over(
+                    &mut flattened,
+                    &Color::from(pixel),
+                    BlendMode::Normal,
+                    1.0,
+                );
+                let pixel: [u8; 4] = flattened.into();
+                self.data[offset..offset + 4].copy_from_slice(&pixel);
+            }
+        }
+    }
+}
+
+// MARK: Hue and saturation
+
+/// The Rec. 709 luma weights of the red, green and blue channels.
+const LUMA_WEIGHTS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+impl Image {
+    /// Rotates the hue of every pixel by a number of degrees.
+    ///
+    /// Rather than converting each pixel to HSB and back, this rotates the
+    /// colours about the grey axis with a single matrix, which is the same
+    /// approach as the SVG `hueRotate` filter. Unlike SVG, which uses
+    /// rounded constants for some terms, the whole matrix is derived from
+    /// the Rec. 709 luma weights, so each pixel keeps its luma, although
+    /// saturated colours may be clipped. The alpha channel is preserved.
+    pub fn rotate_hue(&mut self, degrees: f32) {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let [r, g, b] = LUMA_WEIGHTS;
+        // The sine terms of the green row are chosen so that the weighted
+        // sum of each column’s sine terms is zero, which keeps the luma,
+        // and so that the row sums to zero, which keeps greys unchanged.
+        let green_sin = [
+            (r * r + b * (1.0 - r)) / g,
+            r - b,
+            -(r * (1.0 - b) + b * b) / g,
+        ];
+        let matrix = [
+            [
+                r + cos * (1.0 - r) - sin * r,
+                g - cos * g - sin * g,
+                b - cos * b + sin * (1.0 - b),
+            ],
+            [
+                r - cos * r + sin * green_sin[0],
+                g + cos * (1.0 - g) + sin * green_sin[1],
+                b - cos * b + sin * green_sin[2],
+            ],
+            [
+                r - cos * r - sin * (1.0 - r),
+                g - cos * g + sin * g,
+                b + cos * (1.0 - b) + sin * b,
+            ],
+        ];
+        self.apply_color_matrix(matrix);
+    }
+
+    /// Scales the saturation of every pixel by `factor`, moving each colour
+    /// towards or away from the grey of the same Rec. 709 luma. A factor of
+    /// 0 gives greyscale, 1 leaves the image unchanged, and larger values
+    /// increase the saturation. The alpha channel is preserved.
+    pub fn adjust_saturation(&mut self, factor: f32) {
+        let matrix = [0, 1, 2].map(|row| {
+            [0, 1, 2].map(|column| {
+                let identity = if row == column { 1.0 } else { 0.0 };
+                LUMA_WEIGHTS[column] * (1.0 - factor) + identity * factor
+            })
+        });
+        self.apply_color_matrix(matrix);
+    }
+
+    /// Multiplies the colour channels of every pixel by a 3 × 3 matrix,
+    /// clamping the results. The alpha channel is preserved.
+    fn apply_color_matrix(&mut self, matrix: [[f32; 3]; 3]) {
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact_mut(4) {
+                let input = [pixel[0], pixel[1], pixel[2]].map(|value| value as f32);
+                for (value, row) in pixel.iter_mut().zip(&matrix) {
+                    let output: f32 = row.iter().zip(&input).map(|(a, b)| a * b).sum();
+                    *value = output.round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+// MARK: Bloom
+
+impl Image {
+    /// Adds a bloom, or glow, effect to the image. Pixels with a luma of at
+    /// least `threshold` are extracted, blurred with a gaussian of standard
+    /// deviation `radius`, and then screen blended back over the image.
+    /// The glow’s alpha is scaled by `intensity`, so its contribution is
+    /// proportional to `intensity` until the glow becomes fully opaque.
+    pub fn bloom(&mut self, threshold: u8, intensity: f32, radius: f32) {
+        let mut glow = Image::empty(self.size);
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let location = Point { x, y };
+                let Some(color) = self.pixel_color(location.into()) else {
+                    continue;
+                };
+                if color.grayscale().red >= threshold {
+                    glow.set_pixel_color(color, location);
+                }
+            }
+        }
+
+        glow.gaussian_blur(radius, EdgeMode::Clamp);
+
+        // Only the alpha is scaled, as the colour is already weighted by
+        // the alpha when it is blended.
+        let intensity = intensity.max(0.0);
+        for pixel in glow.data.chunks_exact_mut(4) {
+            pixel[3] = (pixel[3] as f32 * intensity).round().min(255.0) as u8;
+        }
+
+        let mut layer = Layer::new(&glow, Point::zero());
+        layer.blend_mode = BlendMode::Screen;
+        composite::draw_layer_over_image(self, &layer);
+    }
+}
+
+// MARK: Blurring
+
+impl Image {
+    /// Blurs the image with a box blur, averaging each pixel with the
+    /// pixels within `radius` of it horizontally and vertically. The blur is
+    /// applied as two separable passes, so the cost does not depend on the
+    /// radius. Colours are blurred with premultiplied alpha, so transparent
+    /// pixels do not darken the edges. Samples outside of the image are
+    /// handled according to `edge_mode`, where zero is transparent.
+    pub fn box_blur(&mut self, radius: u32, edge_mode: EdgeMode) {
+        if radius == 0 {
+            return;
+        }
+        let mut channels = self.premultiplied_channels();
+        box_blur_channels(&mut channels, self.size, radius, edge_mode);
+        self.set_premultiplied_channels(&channels);
+    }
+
+    /// Blurs the image with three box blurs, which closely approximates a
+    /// gaussian blur with the supplied standard deviation. Samples outside
+    /// of the image are handled according to `edge_mode`.
+    pub fn gaussian_blur(&mut self, sigma: f32, edge_mode: EdgeMode) {
+        if sigma <= 0.0 {
+            return;
+        }
+        let mut channels = self.premultiplied_channels();
+        for radius in gaussian_box_radii(sigma) {
+            box_blur_channels(&mut channels, self.size, radius, edge_mode);
+        }
+        self.set_premultiplied_channels(&channels);
+    }
+
+    /// Returns the tightly packed pixel data as premultiplied floating
+    /// point channels.
+    fn premultiplied_channels(&self) -> Vec<f32> {
+        let mut channels = Vec::with_capacity((self.size.width * self.size.height * 4) as usize);
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact(4) {
+                let alpha = pixel[3] as f32 / 255.0;
+                channels.push(pixel[0] as f32 * alpha);
+                channels.push(pixel[1] as f32 * alpha);
+                channels.push(pixel[2] as f32 * alpha);
+                channels.push(pixel[3] as f32);
+            }
+        }
+        channels
+    }
+
+    /// Replaces the pixel data with tightly packed, premultiplied floating
+    /// point channels, unpremultiplying them in the process.
+    fn set_premultiplied_channels(&mut self, channels: &[f32]) {
+        let width = self.size.width as usize;
+        for (index, pixel) in channels.chunks_exact(4).enumerate() {
+            let offset = (index / width) * self.bytes_per_row as usize + (index % width) * 4;
+            let alpha = pixel[3].round().clamp(0.0, 255.0);
+            let unpremultiply = |value: f32| {
+                if alpha == 0.0 {
+                    0
+                } else {
+                    // Using the unrounded alpha, as rounding it first
+                    // shifts the colour of faint pixels.
+                    (value * 255.0 / pixel[3]).round().clamp(0.0, 255.0) as u8
+                }
+            };
+            self.data[offset] = unpremultiply(pixel[0]);
+            self.data[offset + 1] = unpremultiply(pixel[1]);
+            self.data[offset + 2] = unpremultiply(pixel[2]);
+            self.data[offset + 3] = alpha as u8;
+        }
+    }
+}
+
+// MARK: Convolution
+
+impl Image {
+    /// Applies a 3 x 3 convolution kernel to the colour channels of the
+    /// image. The kernel is in row-major order, so index 4 is the weight
+    /// of the pixel itself, indices 0 to 2 are the row above and 6 to 8
+    /// are the row below. Each weighted sum is divided by `divisor`,
+    /// offset by `bias` and clamped between 0 and 255. A divisor of zero
+    /// is treated as one. Samples outside of the image are handled
+    /// according to `edge_mode`, where zero is black, and the alpha
+    /// channel is unchanged.
+    pub fn convolve_3x3(&mut self, kernel: [f32; 9], divisor: f32, bias: f32, edge_mode: EdgeMode) {
+        let source = self.clone();
+        let width = self.size.width as i32;
+        let height = self.size.height as i32;
+        let divisor = if divisor == 0.0 { 1.0 } else { divisor };
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sums = [0.0; 3];
+                for (index, weight) in kernel.iter().enumerate() {
+                    let sample_x = edge_mode.resolve(x + index as i32 % 3 - 1, width);
+                    let sample_y = edge_mode.resolve(y + index as i32 / 3 - 1, height);
+                    let (Some(sample_x), Some(sample_y)) = (sample_x, sample_y) else {
+                        continue;
+                    };
+                    let offset = sample_y * source.bytes_per_row as usize + sample_x * 4;
+                    for (channel, sum) in sums.iter_mut().enumerate() {
+                        *sum += source.data[offset + channel] as f32 * weight;
+                    }
+                }
+
+                let offset = y as usize * self.bytes_per_row as usize + x as usize * 4;
+                for (channel, sum) in sums.iter().enumerate() {
+                    self.data[offset + channel] =
+                        (sum / divisor + bias).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// Sharpens the image by increasing the contrast between each pixel
+    /// and its horizontal and vertical neighbours.
+    pub fn sharpen(&mut self) {
+        self.convolve_3x3(
+            [0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0],
+            1.0,
+            0.0,
+            EdgeMode::Clamp,
+        );
+    }
+
+    /// Embosses the image, giving it a raised appearance lit from the top
+    /// left. Flat areas keep their colour.
+    pub fn emboss(&mut self) {
+        self.convolve_3x3(
+            [-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0],
+            1.0,
+            0.0,
+            EdgeMode::Clamp,
+        );
+    }
+}
+
+// MARK: Edge handling
+
+/// How filters sample locations outside of the image.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeMode {
+    /// Uses the nearest pixel on the edge of the image.
+    #[default]
+    Clamp,
+    /// Uses the pixel on the opposite edge, as if the image were tiled.
+    /// Best for seamless textures.
+    Wrap,
+    /// Treats the pixels outside of the image as zero.
+    Zero,
+    /// Reflects the image about its edges, without repeating the edge
+    /// pixels themselves.
+    Mirror,
+}
+
+impl EdgeMode {
+    /// Returns the index within `0..length` to sample for a position on
+    /// a line of pixels, or `None` if the sample is zero.
+    fn resolve(&self, position: i32, length: i32) -> Option<usize> {
+        if length <= 0 {
+            return None;
+        }
+        if (0..length).contains(&position) {
+            return Some(position as usize);
+        }
+        let index = match self {
+            EdgeMode::Clamp => position.clamp(0, length - 1),
+            EdgeMode::Wrap => position.rem_euclid(length),
+            EdgeMode::Zero => return None,
+            EdgeMode::Mirror => {
+                if length == 1 {
+                    0
+                } else {
+                    let period = (length - 1) * 2;
+                    let index = position.rem_euclid(period);
+                    if index < length {
+                        index
+                    } else {
+                        period - index
+                    }
+                }
+            }
+        };
+        Some(index as usize)
+    }
+}
+
+// MARK: Helper methods
+
+/// Replaces the RGBA bytes of a pixel with the colour returned by `f`.
+fn map_pixel<F>(pixel: &mut [u8], f: F)
+where
+    F: FnOnce(Color) -> Color,
+{
+    let color = Color::from(<[u8; 4]>::try_from(&*pixel).unwrap());
+    let color: [u8; 4] = f(color).into();
+    pixel.copy_from_slice(&color);
+}
+
+/// Returns the radii of three box blurs that approximate a gaussian blur.
+/// Based on http://blog.ivank.net/fastest-gaussian-blur.html
+fn gaussian_box_radii(sigma: f32) -> [u32; 3] {
+    let passes = 3.0;
+    let ideal_width = (12.0 * sigma * sigma / passes + 1.0).sqrt();
+    let mut lower_width = ideal_width.floor() as i32;
+    if lower_width % 2 == 0 {
+        lower_width -= 1;
+    }
+    let upper_width = lower_width + 2;
+    let lower_width_f = lower_width as f32;
+    let lower_count = ((12.0 * sigma * sigma
+        - passes * lower_width_f * lower_width_f
+        - 4.0 * passes * lower_width_f
+        - 3.0 * passes)
+        / (-4.0 * lower_width_f - 4.0))
+        .round() as i32;
+    let radius = |pass: i32| {
+        let width = if pass < lower_count {
+            lower_width
+        } else {
+            upper_width
+        };
+        ((width - 1) / 2).max(0) as u32
+    };
+    [radius(0), radius(1), radius(2)]
+}
+
+/// Blurs tightly packed channels with a horizontal and then a vertical box
+/// blur, sampling outside of the image according to the edge mode.
+fn box_blur_channels(channels: &mut [f32], size: Size<u32>, radius: u32, edge_mode: EdgeMode) {
+    if radius == 0 {
+        return;
+    }
+    let width = size.width as usize;
+    let height = size.height as usize;
+    let mut buffer = vec![0.0; channels.len()];
+
+    // Horizontal pass.
+    for y in 0..height {
+        let row = y * width;
+        blur_line(
+            channels,
+            &mut buffer,
+            width,
+            radius as i32,
+            edge_mode,
+            |x| (row + x) * 4,
+        );
+    }
+    // Vertical pass.
+    for x in 0..width {
+        blur_line(&buffer, channels, height, radius as i32, edge_mode, |y| {
+            (y * width + x) * 4
+        });
+    }
+}
+
+/// Box blurs a single line of pixels from the source into the destination
+/// using a sliding window. The offset closure maps a position on the line
+/// to the index of the first channel of that pixel.
+fn blur_line<F>(
+    source: &[f32],
+    destination: &mut [f32],
+    length: usize,
+    radius: i32,
+    edge_mode: EdgeMode,
+    offset: F,
+) where
+    F: Fn(usize) -> usize,
+{
+    let sample = |position: i32, channel: usize| {
+        edge_mode
+            .resolve(position, length as i32)
+            .map_or(0.0, |index| source[offset(index) + channel])
+    };
+    let window = (radius * 2 + 1) as f32;
+
+    let mut sums = [0.0f32; 4];
+    for position in -radius..=radius {
+        for (channel, sum) in sums.iter_mut().enumerate() {
+            *sum += sample(position, channel);
+        }
+    }
+
+    for position in 0..length as i32 {
+        let index = offset(position as usize);
+        for channel in 0..4 {
+            destination[index + channel] = sums[channel] / window;
+        }
+        for (channel, sum) in sums.iter_mut().enumerate() {
+            *sum += sample(position + radius + 1, channel) - sample(position - radius, channel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        composite::{self, Layer},
+        BlendMode, Color, Image, Point, Size,
+    };
+
+    use super::{curve_from_points, EdgeMode};
+
+    #[test]
+    fn shift_hue_except() {
+        let skin = Color::from_rgb_u32(0xe8b796);
+        let mut image = Image::color(
+            &Color::RED,
+            Size {
+                width: 3,
+                height: 1,
+            },
+        );
+        image.set_pixel_color(skin.clone(), Point { x: 1, y: 0 });
+        let nearly_skin = Color::from_rgb_u32(0xe9b695);
+        image.set_pixel_color(nearly_skin.clone(), Point { x: 2, y: 0 });
+
+        image.shift_hue_except(120.0, &skin, 2);
+
+        assert_eq!(image.pixel_color(Point { x: 0, y: 0 }), Some(Color::GREEN));
+        assert_eq!(image.pixel_color(Point { x: 1, y: 0 }), Some(skin));
+        assert_eq!(image.pixel_color(Point { x: 2, y: 0 }), Some(nearly_skin));
+    }
+
+    #[test]
+    fn map_pixels() {
+        let mut image = Image::empty_aligned(
+            Size {
+                width: 3,
+                height: 2,
+            },
+            16,
+        );
+        image.set_pixel_color(Color::RED, Point { x: 2, y: 1 });
+        let padding = image.data[12..16].to_vec();
+
+        image.map_pixels(|color| Color {
+            green: 0x80,
+            ..color
+        });
+
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgba_u32(0x00800000))
+        );
+        assert_eq!(
+            image.pixel_color(Point { x: 2, y: 1 }),
+            Some(Color::from_rgb_u32(0xff8000))
+        );
+        assert_eq!(image.data[12..16], padding);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn map_pixels_parallel() {
+        let image = Image::open("tests/images/gerbil.jpg").unwrap();
+        let mut padded = Image::empty_aligned(image.size, 64);
+        padded.draw_image_over(&image, Point::zero());
+        let invert = |color: Color| Color {
+            red: 255 - color.red,
+            green: 255 - color.green,
+            blue: 255 - color.blue,
+            alpha: color.alpha,
+        };
+
+        for image in [image, padded] {
+            let mut serial = image.clone();
+            serial.map_pixels(invert);
+            let mut parallel = image.clone();
+            parallel.map_pixels_parallel(invert);
+
+            assert!(parallel == serial);
+            assert!(parallel != image);
+        }
+    }
+
+    #[test]
+    fn apply_curves() {
+        let original = Image::open("tests/images/avatar.png").unwrap();
+        let identity = curve_from_points(&[]);
+        assert_eq!(identity, curve_from_points(&[(0, 0), (255, 255)]));
+        let mut image = original.clone();
+        image.apply_curves(&identity, &identity, &identity);
+        assert_eq!(image, original);
+
+        let invert = curve_from_points(&[(0, 255), (255, 0)]);
+        assert!((0..256).all(|index| invert[index] == 255 - index as u8));
+        image.apply_curves(&invert, &invert, &invert);
+        let mut expected = original.clone();
+        expected.invert_colors(false);
+        assert_eq!(image, expected);
+
+        let mut image = original.clone();
+        image.apply_curves(&invert, &identity, &identity);
+        let color = image.pixel_color(Point { x: 0, y: 0 }).unwrap();
+        let original_color = original.pixel_color(Point { x: 0, y: 0 }).unwrap();
+        assert_eq!(color.red, 255 - original_color.red);
+        assert_eq!(color.green, original_color.green);
+        assert_eq!(color.alpha, original_color.alpha);
+    }
+
+    #[test]
+    fn curve_from_control_points() {
+        let curve = curve_from_points(&[(64, 32), (192, 224), (128, 128)]);
+        assert_eq!(curve[0], 32);
+        assert_eq!(curve[64], 32);
+        assert_eq!(curve[128], 128);
+        assert_eq!(curve[192], 224);
+        assert_eq!(curve[255], 224);
+        assert!(curve.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(curve[96] < 96 && curve[160] > 160);
+
+        // Flat sections don’t overshoot.
+        let curve = curve_from_points(&[(0, 0), (100, 200), (150, 200), (255, 255)]);
+        assert!(curve[100..=150].iter().all(|value| *value == 200));
+        assert!(curve.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        assert_eq!(curve_from_points(&[(10, 50)]), [50; 256]);
+        assert_eq!(curve_from_points(&[(10, 50), (10, 60)]), [60; 256]);
+    }
+
+    #[test]
+    fn luma_map() {
+        let mut blue = Color::BLUE;
+        blue.alpha = 0x80;
+        let image = Image::color(
+            &blue,
+            Size {
+                width: 2,
+                height: 2,
+            },
+        );
+
+        let result = image.luma_map();
+
+        let expected_color = Color {
+            red: 18,
+            green: 18,
+            blue: 18,
+            alpha: 0x80,
+        };
+        assert_eq!(result, Image::color(&expected_color, image.size));
+    }
+
+    #[test]
+    fn apply_color() {
+        let size = Size {
+            width: 3,
+            height: 1,
+        };
+        let mut image = Image::color(&Color::WHITE, size);
+        image.set_pixel_color(Color::from_rgb_u32(0x4080c0), Point { x: 1, y: 0 });
+        image.set_pixel_color(Color::GREEN.with_alpha(0x80), Point { x: 2, y: 0 });
+
+        let mut expected = image.clone();
+        let red = Image::color(&Color::RED, size);
+        let mut layer = Layer::new(&red, Point::zero());
+        layer.blend_mode = BlendMode::Multiply;
+        layer.opacity = 0.5;
+        composite::draw_layer_over_image(&mut expected, &layer);
+
+        image.apply_color(&Color::RED, BlendMode::Multiply, 0.5);
+
+        assert_eq!(image, expected);
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgb_u32(0xff8080))
+        );
+    }
+
+    #[test]
+    fn flatten_onto() {
+        let mut image = Image::empty(Size {
+            width: 3,
+            height: 1,
+        });
+        image.set_pixel_color(Color::RED.with_alpha(0x80), Point { x: 0, y: 0 });
+        image.set_pixel_color(Color::BLUE, Point { x: 1, y: 0 });
+
+        image.flatten_onto(&Color::WHITE);
+
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgb_u32(0xff7f7f))
+        );
+        assert_eq!(image.pixel_color(Point { x: 1, y: 0 }), Some(Color::BLUE));
+        assert_eq!(image.pixel_color(Point { x: 2, y: 0 }), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn bloom() {
+        let size = Size {
+            width: 9,
+            height: 9,
+        };
+        let mut image = Image::color(&Color::BLACK, size);
+        let center = Point { x: 4, y: 4 };
+        image.set_pixel_color(Color::WHITE, center);
+
+        image.bloom(200, 1.0, 1.5);
+
+        let pixel = |x: i32, y: i32| image.pixel_color(Point { x, y }).unwrap();
+
+        // The bright spot stays bright.
+        assert_eq!(pixel(4, 4).red, 0xff);
+        // The glow spreads evenly around it, fading with distance.
+        let neighbour = pixel(5, 4);
+        assert!(neighbour.red > 0);
+        assert_eq!(neighbour, pixel(3, 4));
+        assert_eq!(neighbour, pixel(4, 5));
+        assert_eq!(neighbour, pixel(4, 3));
+        assert!(pixel(6, 4).red < neighbour.red);
+        // The image stays opaque and the far corners are untouched.
+        assert_eq!(neighbour.alpha, 0xff);
+        assert_eq!(pixel(0, 0), Color::BLACK);
+
+        // The glow’s contribution is proportional to the intensity.
+        let mut half_image = Image::color(&Color::BLACK, size);
+        half_image.set_pixel_color(Color::WHITE, center);
+        half_image.bloom(200, 0.5, 1.5);
+        let half_neighbour = half_image.pixel_color(Point { x: 5, y: 4 }).unwrap();
+        assert!(half_neighbour.red.abs_diff(neighbour.red / 2) <= 1);
+        assert_eq!(half_image.pixel_color(center.into()), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn box_blur() {
+        let size = Size {
+            width: 5,
+            height: 5,
+        };
+        let mut image = Image::empty(size);
+        image.set_pixel_color(Color::WHITE, Point { x: 2, y: 2 });
+
+        image.box_blur(1, EdgeMode::Clamp);
+
+        let spread = Color::WHITE.with_alpha(28);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if (1..4).contains(&x) && (1..4).contains(&y) {
+                    spread.clone()
+                } else {
+                    Color::CLEAR
+                };
+                assert_eq!(image.pixel_color(Point { x, y }), Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn box_blur_edge_modes() {
+        let mut image = Image::color(
+            &Color::BLACK,
+            Size {
+                width: 4,
+                height: 3,
+            },
+        );
+        for y in 0..3 {
+            image.set_pixel_color(Color::WHITE, Point { x: 0, y });
+        }
+        let blurred = |edge_mode: EdgeMode| {
+            let mut result = image.clone();
+            result.box_blur(1, edge_mode);
+            [0, 3].map(|x| result.pixel_color(Point { x, y: 1 }).unwrap())
+        };
+
+        // Clamping repeats the white edge pixel, and nothing wraps around.
+        assert_eq!(
+            blurred(EdgeMode::Clamp),
+            [Color::from_rgb_u32(0xaaaaaa), Color::BLACK]
+        );
+        // Wrapping pulls the white pixel across to the far edge.
+        assert_eq!(
+            blurred(EdgeMode::Wrap),
+            [Color::from_rgb_u32(0x555555), Color::from_rgb_u32(0x555555)]
+        );
+        // Zero padding is transparent, so the edges fade out.
+        assert_eq!(
+            blurred(EdgeMode::Zero),
+            [
+                Color::from_rgba_u32(0x808080aa),
+                Color::BLACK.with_alpha(0xaa)
+            ]
+        );
+        // Mirroring reflects the neighbouring black pixel.
+        assert_eq!(
+            blurred(EdgeMode::Mirror),
+            [Color::from_rgb_u32(0x555555), Color::BLACK]
+        );
+    }
+
+    #[test]
+    fn edge_mode_resolve() {
+        let resolve =
+            |edge_mode: EdgeMode| (-3..7).map(move |position| edge_mode.resolve(position, 4));
+        let clamp: Vec<_> = resolve(EdgeMode::Clamp).map(Option::unwrap).collect();
+        assert_eq!(clamp, [0, 0, 0, 0, 1, 2, 3, 3, 3, 3]);
+        let wrap: Vec<_> = resolve(EdgeMode::Wrap).map(Option::unwrap).collect();
+        assert_eq!(wrap, [1, 2, 3, 0, 1, 2, 3, 0, 1, 2]);
+        let mirror: Vec<_> = resolve(EdgeMode::Mirror).map(Option::unwrap).collect();
+        assert_eq!(mirror, [3, 2, 1, 0, 1, 2, 3, 2, 1, 0]);
+        let zero: Vec<_> = resolve(EdgeMode::Zero).collect();
+        assert_eq!(
+            zero,
+            [
+                None,
+                None,
+                None,
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(3),
+                None,
+                None,
+                None
+            ]
+        );
+        assert_eq!(EdgeMode::Mirror.resolve(-2, 1), Some(0));
+    }
+
+    #[test]
+    fn gaussian_blur() {
+        let size = Size {
+            width: 9,
+            height: 9,
+        };
+        let mut image = Image::empty(size);
+        image.set_pixel_color(Color::WHITE, Point { x: 4, y: 4 });
+
+        image.gaussian_blur(1.5, EdgeMode::Clamp);
+
+        let pixel = |x: i32, y: i32| image.pixel_color(Point { x, y }).unwrap();
+        let neighbour = pixel(5, 4);
+        assert_eq!(neighbour, pixel(3, 4));
+        assert_eq!(neighbour, pixel(4, 3));
+        assert_eq!(neighbour, pixel(4, 5));
+        assert!(pixel(4, 4).alpha > neighbour.alpha);
+        assert!(neighbour.alpha > pixel(6, 4).alpha);
+        assert_eq!(neighbour.red, 0xff);
+    }
+
+    #[test]
+    fn convolve_3x3() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+
+        let mut result = image.clone();
+        result.convolve_3x3(
+            [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+            1.0,
+            0.0,
+            EdgeMode::Clamp,
+        );
+        assert_eq!(result, image);
+
+        let size = Size {
+            width: 3,
+            height: 1,
+        };
+        let mut image = Image::color(&Color::from_rgb_u32(0x404040), size);
+        image.set_pixel_color(Color::from_rgb_u32(0x808080), Point { x: 1, y: 0 });
+        image.sharpen();
+        assert_eq!(image.pixel_color(Point { x: 0, y: 0 }), Some(Color::BLACK));
+        assert_eq!(image.pixel_color(Point { x: 1, y: 0 }), Some(Color::WHITE));
+        assert_eq!(image.pixel_color(Point { x: 2, y: 0 }), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn threshold() {
+        let mut image = Image::empty(Size {
+            width: 256,
+            height: 1,
+        });
+        for x in 0..256 {
+            let value = x as u8;
+            let color = Color {
+                red: value,
+                green: value,
+                blue: value,
+                alpha: 0x80,
+            };
+            image.set_pixel_color(color, Point { x, y: 0 });
+        }
+
+        image.threshold(128);
+
+        for x in 0..256 {
+            let expected = if x < 128 { Color::BLACK } else { Color::WHITE };
+            assert_eq!(
+                image.pixel_color(Point { x, y: 0 }),
+                Some(expected.with_alpha(0x80))
+            );
+        }
+    }
+
+    #[test]
+    fn posterize() {
+        let mut image = Image::empty(Size {
+            width: 4,
+            height: 1,
+        });
+        image.set_pixel_color(Color::from_rgba_u32(0x10407f80), Point { x: 0, y: 0 });
+        image.set_pixel_color(Color::from_rgb_u32(0x80c0ff), Point { x: 1, y: 0 });
+
+        image.posterize(3);
+
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgba_u32(0x00008080))
+        );
+        assert_eq!(
+            image.pixel_color(Point { x: 1, y: 0 }),
+            Some(Color::from_rgb_u32(0x80ffff))
+        );
+    }
+
+    #[test]
+    fn quantize_alpha() {
+        let mut image = Image::empty(Size {
+            width: 256,
+            height: 1,
+        });
+        for x in 0..256 {
+            let color = Color::RED.with_alpha(x as u8);
+            image.set_pixel_color(color, Point { x, y: 0 });
+        }
+
+        let mut two_levels = image.clone();
+        two_levels.quantize_alpha(2);
+        for x in 0..256 {
+            let color = two_levels.pixel_color(Point { x, y: 0 }).unwrap();
+            let expected_alpha = if x < 128 { 0 } else { 255 };
+            assert_eq!(color, Color::RED.with_alpha(expected_alpha));
+        }
+
+        image.quantize_alpha(5);
+        let alphas: HashSet<u8> = image
+            .colors()
+            .into_iter()
+            .map(|color| color.alpha)
+            .collect();
+        assert_eq!(alphas, HashSet::from([0, 64, 128, 191, 255]));
+    }
+
+    #[test]
+    fn rotate_hue() {
+        let size = Size {
+            width: 2,
+            height: 2,
+        };
+        let mut image = Image::color(&Color::RED, size);
+        image.rotate_hue(120.0);
+        let color = image.pixel_color(Point { x: 1, y: 1 }).unwrap();
+        assert!((color.hue() - 1.0 / 3.0).abs() < 0.01);
+        assert!(color.green > 0 && color.red == 0 && color.blue == 0);
+
+        // Colours that aren’t clipped keep their luma.
+        let muted_red = Color::from_rgb_u32(0x906c6c);
+        let mut image = Image::color(&muted_red, size);
+        image.rotate_hue(120.0);
+        let color = image.pixel_color(Point::zero()).unwrap();
+        assert!((color.hue() - 1.0 / 3.0).abs() < 0.01);
+        assert!(color
+            .grayscale()
+            .is_within_tolerance_of(&muted_red.grayscale(), 1));
+        for degrees in [45.0, 200.0, 300.0] {
+            let mut image = Image::color(&muted_red, size);
+            image.rotate_hue(degrees);
+            let color = image.pixel_color(Point::zero()).unwrap();
+            assert!(color
+                .grayscale()
+                .is_within_tolerance_of(&muted_red.grayscale(), 1));
+        }
+
+        let original = Image::open("tests/images/avatar.png").unwrap();
+        let mut image = original.clone();
+        image.rotate_hue(0.0);
+        assert_eq!(image, original);
+        image.rotate_hue(360.0);
+        assert_eq!(image, original);
+    }
+
+    #[test]
+    fn adjust_saturation() {
+        let original = Image::open("tests/images/avatar.png").unwrap();
+        let mut image = original.clone();
+        image.adjust_saturation(1.0);
+        assert_eq!(image, original);
+
+        image.adjust_saturation(0.0);
+        let mut greyscale = original.clone();
+        greyscale.grayscale();
+        for color in image.colors() {
+            assert_eq!(color.red, color.green);
+            assert_eq!(color.green, color.blue);
+        }
+        for y in 0..image.size.height as i32 {
+            for x in 0..image.size.width as i32 {
+                let location = Point { x, y };
+                let color = image.pixel_color(location).unwrap();
+                let expected = greyscale.pixel_color(location).unwrap();
+                assert!(color.is_within_tolerance_of(&expected, 1));
+            }
+        }
+
+        let mut image = Image::color(
+            &Color::from_rgb_u32(0xa06060),
+            Size {
+                width: 1,
+                height: 1,
+            },
+        );
+        image.adjust_saturation(2.0);
+        let color = image.pixel_color(Point::zero()).unwrap();
+        assert!(color.red > 0xa0 && color.green < 0x60);
+        assert_eq!(color.green, color.blue);
+    }
+
+    #[test]
+    fn invert_colors() {
+        let mut image = Image::empty(Size {
+            width: 2,
+            height: 1,
+        });
+        image.set_pixel_color(Color::from_rgba_u32(0x20406080), Point { x: 0, y: 0 });
+        image.set_pixel_color(Color::WHITE, Point { x: 1, y: 0 });
+        let original = image.clone();
+
+        image.invert_colors(false);
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgba_u32(0xdfbf9f80))
+        );
+        assert_eq!(image.pixel_color(Point { x: 1, y: 0 }), Some(Color::BLACK));
+        image.invert_colors(false);
+        assert_eq!(image, original);
+
+        image.invert_colors(true);
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgba_u32(0xdfbf9f7f))
+        );
+        assert_eq!(
+            image.pixel_color(Point { x: 1, y: 0 }),
+            Some(Color::BLACK.with_alpha(0))
+        );
+        image.invert_colors(true);
+        assert_eq!(image, original);
+    }
+
+    #[test]
+    fn adjust_brightness_contrast() {
+        let mut image = Image::empty(Size {
+            width: 2,
+            height: 1,
+        });
+        image.set_pixel_color(Color::from_rgba_u32(0x404040ff), Point { x: 0, y: 0 });
+        image.set_pixel_color(Color::from_rgba_u32(0xc0c0c080), Point { x: 1, y: 0 });
+        let original = image.clone();
+
+        image.adjust_brightness_contrast(0.0, 1.0);
+        assert_eq!(image, original);
+
+        image.adjust_brightness_contrast(0.0, 2.0);
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgba_u32(0x010101ff))
+        );
+        assert_eq!(
+            image.pixel_color(Point { x: 1, y: 0 }),
+            Some(Color::from_rgba_u32(0xffffff80))
+        );
+
+        let mut image = original.clone();
+        image.adjust_brightness_contrast(0.5, 1.0);
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgba_u32(0xc0c0c0ff))
+        );
+        assert_eq!(
+            image.pixel_color(Point { x: 1, y: 0 }),
+            Some(Color::from_rgba_u32(0xffffff80))
+        );
+    }
+
+    #[test]
+    fn grayscale() {
+        let mut image = Image::color(
+            &Color::RED.with_alpha(0x80),
+            Size {
+                width: 2,
+                height: 1,
+            },
+        );
+        image.set_pixel_color(Color::WHITE, Point { x: 1, y: 0 });
+
+        image.grayscale();
+
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgba_u32(0x36363680))
+        );
+        assert_eq!(image.pixel_color(Point { x: 1, y: 0 }), Some(Color::WHITE));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::Color;
+
+use super::Image;
+
+/// A three dimensional colour lookup table, as used for colour grading.
+/// Tables are created with `identity` or `from_cube`, which guarantee a
+/// size of at least 2 and the matching number of colours.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3d {
+    /// The number of entries along each axis of the grid.
+    size: usize,
+    /// The output colours, where red varies fastest, then green, then
+    /// blue. There are `size` cubed colours.
+    colors: Vec<Color>,
+}
+
+// MARK: Creation
+
+impl Lut3d {
+    /// Creates a lookup table that maps every colour to itself.
+    pub fn identity(size: usize) -> Self {
+        let size = size.max(2);
+        let value = |index: usize| (index as f32 * 255.0 / (size - 1) as f32).round() as u8;
+        let mut colors = Vec::with_capacity(size * size * size);
+        for blue in 0..size {
+            for green in 0..size {
+                for red in 0..size {
+                    colors.push(Color {
+                        red: value(red),
+                        green: value(green),
+                        blue: value(blue),
+                        alpha: 255,
+                    });
+                }
+            }
+        }
+        Self { size, colors }
+    }
+
+    /// Loads a lookup table from the contents of an Adobe / Resolve
+    /// `.cube` file. Only 3D tables with the default domain of 0 to 1
+    /// are supported.
+    pub fn from_cube(data: &str) -> anyhow::Result<Self> {
+        let mut size = None;
+        let mut colors = Vec::new();
+
+        for line in data.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            let values: Vec<&str> = words.collect();
+            match keyword {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let Some(value) = values.first() else {
+                        anyhow::bail!("Missing LUT size.");
+                    };
+                    let value: usize = value.parse()?;
+                    if value < 2 {
+                        anyhow::bail!("The LUT size must be at least 2.");
+                    }
+                    size = Some(value);
+                }
+                "LUT_1D_SIZE" => anyhow::bail!("1D LUTs are not supported."),
+                "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                    let expected = if keyword == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                    for value in values {
+                        if value.parse::<f32>()? != expected {
+                            anyhow::bail!("Only LUTs with a domain of 0 to 1 are supported.");
+                        }
+                    }
+                }
+                _ => {
+                    let channels = std::iter::once(keyword)
+                        .chain(values)
+                        .map(|value| value.parse::<f32>())
+                        .collect::<Result<Vec<f32>, _>>()?;
+                    let [red, green, blue] = channels[..] else {
+                        anyhow::bail!("Invalid LUT entry: {line}");
+                    };
+                    let channel = |value: f32| (value * 255.0).round().clamp(0.0, 255.0) as u8;
+                    colors.push(Color {
+                        red: channel(red),
+                        green: channel(green),
+                        blue: channel(blue),
+                        alpha: 255,
+                    });
+                }
+            }
+        }
+
+        let Some(size) = size else {
+            anyhow::bail!("Missing LUT size.");
+        };
+        let expected_count = size
+            .checked_pow(3)
+            .ok_or(anyhow::anyhow!("The LUT size is too large."))?;
+        if colors.len() != expected_count {
+            anyhow::bail!(
+                "Expected {} LUT entries, but found {}.",
+                expected_count,
+                colors.len()
+            );
+        }
+        Ok(Self { size, colors })
+    }
+}
+
+// MARK: Properties
+
+impl Lut3d {
+    /// Returns the number of entries along each axis of the grid.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the output colours, where red varies fastest, then green,
+    /// then blue. There are `size` cubed colours.
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+}
+
+// MARK: Sampling
+
+impl Lut3d {
+    /// Returns the colour for an input colour, interpolating between the
+    /// eight nearest entries in the table. The alpha channel is preserved.
+    pub fn sample(&self, color: &Color) -> Color {
+        let scale = (self.size - 1) as f32 / 255.0;
+        let coordinates = [color.red, color.green, color.blue].map(|value| {
+            let position = value as f32 * scale;
+            let lower = (position.floor() as usize).min(self.size - 2);
+            (lower, position - lower as f32)
+        });
+        let [(red, red_fraction), (green, green_fraction), (blue, blue_fraction)] = coordinates;
+
+        let mut totals = [0.0f32; 3];
+        for corner in 0..8 {
+            let (dr, dg, db) = (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let weight = |offset: usize, fraction: f32| {
+                if offset == 1 {
+                    fraction
+                } else {
+                    1.0 - fraction
+                }
+            };
+            let weight =
+                weight(dr, red_fraction) * weight(dg, green_fraction) * weight(db, blue_fraction);
+            if weight == 0.0 {
+                continue;
+            }
+            let index = ((blue + db) * self.size + green + dg) * self.size + red + dr;
+            let entry = &self.colors[index];
+            totals[0] += entry.red as f32 * weight;
+            totals[1] += entry.green as f32 * weight;
+            totals[2] += entry.blue as f32 * weight;
+        }
+
+        let channel = |total: f32| total.round().clamp(0.0, 255.0) as u8;
+        Color {
+            red: channel(totals[0]),
+            green: channel(totals[1]),
+            blue: channel(totals[2]),
+            alpha: color.alpha,
+        }
+    }
+}
+
+impl Image {
+    /// Maps the colour of every pixel through a 3D lookup table, using
+    /// trilinear interpolation. The alpha channel is preserved.
+    pub fn apply_lut3d(&mut self, lut: &Lut3d) {
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact_mut(4) {
+                let color = Color::from(<[u8; 4]>::try_from(&*pixel).unwrap());
+                let color: [u8; 4] = lut.sample(&color).into();
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, Image};
+
+    use super::Lut3d;
+
+    const INVERT_CUBE: &str = "\
+# An inverting LUT.
+TITLE \"Invert\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0.0 0.0 0.0
+DOMAIN_MAX 1.0 1.0 1.0
+
+1.0 1.0 1.0
+0.0 1.0 1.0
+1.0 0.0 1.0
+0.0 0.0 1.0
+1.0 1.0 0.0
+0.0 1.0 0.0
+1.0 0.0 0.0
+0.0 0.0 0.0
+";
+
+    #[test]
+    fn identity() {
+        let original = Image::open("tests/images/gerbil.jpg").unwrap();
+        for size in [2, 17, 33] {
+            let mut image = original.clone();
+            image.apply_lut3d(&Lut3d::identity(size));
+            assert!(image == original);
+        }
+
+        let lut = Lut3d::identity(1);
+        assert_eq!(lut.size(), 2);
+        assert_eq!(lut.colors().len(), 8);
+    }
+
+    #[test]
+    fn from_cube() {
+        let identity = INVERT_CUBE
+            .lines()
+            .take(5)
+            .chain([
+                "0 0 0", "1 0 0", "0 1 0", "1 1 0", "0 0 1", "1 0 1", "0 1 1", "1 1 1",
+            ])
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(Lut3d::from_cube(&identity).unwrap(), Lut3d::identity(2));
+
+        assert!(Lut3d::from_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3d::from_cube(&format!("LUT_3D_SIZE {}\n", usize::MAX)).is_err());
+        assert!(Lut3d::from_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        assert!(
+            Lut3d::from_cube(&INVERT_CUBE.replace("DOMAIN_MAX 1.0", "DOMAIN_MAX 2.0")).is_err()
+        );
+        assert!(Lut3d::from_cube(&INVERT_CUBE.replace("0.0 0.0 0.0\n", "0.0 0.0\n")).is_err());
+    }
+
+    #[test]
+    fn invert() {
+        let lut = Lut3d::from_cube(INVERT_CUBE).unwrap();
+        let original = Image::open("tests/images/avatar.png").unwrap();
+        let mut image = original.clone();
+
+        image.apply_lut3d(&lut);
+
+        let mut expected = original.clone();
+        expected.invert_colors(false);
+        assert_eq!(image, expected);
+        assert_eq!(
+            lut.sample(&Color::from_rgba_u32(0x20408080)),
+            Color::from_rgba_u32(0xdfbf7f80)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::Size;
+
+use super::Image;
+
+/// The bytes that identify run-length encoded image data.
+const MAGIC: &[u8; 4] = b"GRLE";
+/// The length of the header: the magic bytes, the width and the height.
+const HEADER_LENGTH: usize = 12;
+/// The length of a single run: a count followed by an RGBA pixel.
+const RUN_LENGTH: usize = 5;
+
+impl Image {
+    /// Returns the image encoded using a simple run-length encoding.
+    ///
+    /// The data starts with a header of four magic bytes followed by the
+    /// width and height as little endian `u32`s. Each run of identical
+    /// pixels is then stored as a count between 1 and 255 followed by the
+    /// RGBA bytes of the pixel.
+    pub fn to_rle_bytes(&self) -> Vec<u8> {
+        let mut output = Vec::with_capacity(HEADER_LENGTH);
+        output.extend_from_slice(MAGIC);
+        output.extend_from_slice(&self.size.width.to_le_bytes());
+        output.extend_from_slice(&self.size.height.to_le_bytes());
+
+        let mut current: Option<(&[u8], u8)> = None;
+        for y in 0..self.size.height as usize {
+            let row_start = y * self.bytes_per_row as usize;
+            let row_end = row_start + self.size.width as usize * 4;
+            for pixel in self.data[row_start..row_end].chunks_exact(4) {
+                match current {
+                    Some((run_pixel, count)) if run_pixel == pixel && count < u8::MAX => {
+                        current = Some((run_pixel, count + 1));
+                    }
+                    _ => {
+                        if let Some((run_pixel, count)) = current {
+                            output.push(count);
+                            output.extend_from_slice(run_pixel);
+                        }
+                        current = Some((pixel, 1));
+                    }
+                }
+            }
+        }
+        if let Some((run_pixel, count)) = current {
+            output.push(count);
+            output.extend_from_slice(run_pixel);
+        }
+
+        output
+    }
+
+    /// Creates an image from data produced by `to_rle_bytes`.
+    pub fn from_rle_bytes(data: &[u8]) -> anyhow::Result<Image> {
+        if data.len() < HEADER_LENGTH || &data[0..4] != MAGIC {
+            anyhow::bail!("The data is not run-length encoded image data.");
+        }
+
+        let width = u32::from_le_bytes(data[4..8].try_into()?);
+        let height = u32::from_le_bytes(data[8..12].try_into()?);
+        let size = Size { width, height };
+        let too_large = || anyhow::anyhow!("The run-length encoded image size is too large.");
+        let bytes_per_row = width.checked_mul(4).ok_or_else(too_large)?;
+        let expected_length = (bytes_per_row as usize)
+            .checked_mul(height as usize)
+            .ok_or_else(too_large)?;
+
+        let runs = &data[HEADER_LENGTH..];
+        if !runs.len().is_multiple_of(RUN_LENGTH) {
+            anyhow::bail!("The run-length encoded data is truncated.");
+        }
+        // Check that the runs could cover the image before allocating
+        // the pixels, as the size in the header is not trusted.
+        if runs.len() / RUN_LENGTH * u8::MAX as usize * 4 < expected_length {
+            anyhow::bail!("The run-length encoded data does not match the image size.");
+        }
+
+        let mut pixels = Vec::with_capacity(expected_length);
+        for run in runs.chunks_exact(RUN_LENGTH) {
+            let count = run[0] as usize;
+            if count == 0 || pixels.len() + count * 4 > expected_length {
+                anyhow::bail!("The run-length encoded data contains an invalid run.");
+            }
+            for _ in 0..count {
+                pixels.extend_from_slice(&run[1..]);
+            }
+        }
+
+        if pixels.len() != expected_length {
+            anyhow::bail!("The run-length encoded data does not match the image size.");
+        }
+
+        Ok(Image::new(pixels, size, bytes_per_row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Image;
+
+    #[test]
+    fn rle_round_trip() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+
+        let data = image.to_rle_bytes();
+        let result = Image::from_rle_bytes(&data).unwrap();
+
+        assert_eq!(result, image);
+        assert!(data.len() < image.data.len());
+    }
+
+    #[test]
+    fn rle_invalid_data() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+        let data = image.to_rle_bytes();
+
+        assert!(Image::from_rle_bytes(&data[..data.len() - 1]).is_err());
+        assert!(Image::from_rle_bytes(&data[1..]).is_err());
+
+        let mut oversized = data[..12].to_vec();
+        oversized[4..12].copy_from_slice(&[0xff; 8]);
+        oversized.extend_from_slice(&data[12..17]);
+        assert!(Image::from_rle_bytes(&oversized).is_err());
+
+        oversized[4..12].copy_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00]);
+        assert!(Image::from_rle_bytes(&oversized).is_err());
+    }
+}
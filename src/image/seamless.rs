@@ -0,0 +1,108 @@
+use crate::Point;
+
+use super::Image;
+
+impl Image {
+    /// Returns whether the image tiles without visible seams, meaning that
+    /// each pixel on the left and top edges is within `tolerance` of the
+    /// opposite pixel on the right and bottom edges.
+    pub fn is_seamless(&self, tolerance: u8) -> bool {
+        let width = self.size.width as i32;
+        let height = self.size.height as i32;
+        let matches =
+            |a: Point<i32>, b: Point<i32>| match (self.pixel_color(a), self.pixel_color(b)) {
+                (Some(a), Some(b)) => a.is_within_tolerance_of(&b, tolerance),
+                _ => true,
+            };
+
+        let rows_match = (0..height).all(|y| matches(Point { x: 0, y }, Point { x: width - 1, y }));
+        let columns_match =
+            (0..width).all(|x| matches(Point { x, y: 0 }, Point { x, y: height - 1 }));
+        rows_match && columns_match
+    }
+
+    /// Makes the image tileable by cross-fading a border of `blend` pixels
+    /// on each edge with the pixels on the opposite edge. The outermost
+    /// pixels become an even mix of both edges, fading back to the
+    /// original image over the width of the border. The border is limited
+    /// to half of the width or height.
+    pub fn make_seamless(&mut self, blend: u32) {
+        let width = self.size.width as i32;
+        let height = self.size.height as i32;
+
+        let horizontal_blend = blend.min(self.size.width / 2) as i32;
+        for y in 0..height {
+            for offset in 0..horizontal_blend {
+                let near = Point { x: offset, y };
+                let far = Point {
+                    x: width - 1 - offset,
+                    y,
+                };
+                self.cross_fade(near, far, offset, horizontal_blend);
+            }
+        }
+
+        let vertical_blend = blend.min(self.size.height / 2) as i32;
+        for x in 0..width {
+            for offset in 0..vertical_blend {
+                let near = Point { x, y: offset };
+                let far = Point {
+                    x,
+                    y: height - 1 - offset,
+                };
+                self.cross_fade(near, far, offset, vertical_blend);
+            }
+        }
+    }
+
+    /// Mixes two pixels on opposite edges together. Pixels on the edges,
+    /// at an offset of 0, are mixed evenly and pixels at an offset of
+    /// `blend` are left unchanged.
+    fn cross_fade(&mut self, near: Point<i32>, far: Point<i32>, offset: i32, blend: i32) {
+        let (Some(near_color), Some(far_color)) = (self.pixel_color(near), self.pixel_color(far))
+        else {
+            return;
+        };
+        let amount = 0.5 * (1.0 - offset as f32 / blend as f32);
+        let new_near_color = near_color.lerp(&far_color, amount);
+        let new_far_color = far_color.lerp(&near_color, amount);
+        self.set_pixel_color(new_near_color, near.into());
+        self.set_pixel_color(new_far_color, far.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, Image, Point, Size};
+
+    #[test]
+    fn make_seamless() {
+        let size = Size {
+            width: 16,
+            height: 8,
+        };
+        let mut image = Image::empty(size);
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let value = (x * 17) as u8;
+                let color = Color {
+                    red: value,
+                    green: value,
+                    blue: (y * 32) as u8,
+                    alpha: 0xff,
+                };
+                image.set_pixel_color(color, Point { x, y });
+            }
+        }
+        assert!(!image.is_seamless(1));
+
+        image.make_seamless(3);
+
+        assert!(image.is_seamless(1));
+        // The middle of the image is untouched.
+        assert_eq!(
+            image.pixel_color(Point { x: 8, y: 4 }),
+            Some(Color::from_rgb_u32(0x888880))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::{ops::Deref, sync::Arc};
+
+use super::Image;
+
+/// An image whose pixel buffer is shared between clones until one of
+/// them is mutated, at which point that clone takes its own copy.
+///
+/// Cloning a shared image only increments a reference count, which makes
+/// it much cheaper than cloning an `Image` when working with large canvases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedImage(Arc<Image>);
+
+impl SharedImage {
+    /// Creates a new shared image.
+    pub fn new(image: Image) -> Self {
+        Self(Arc::new(image))
+    }
+
+    /// Returns a clone of the image that shares the same pixel buffer.
+    pub fn shallow_clone(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns whether or not the pixel buffer is shared with
+    /// another clone.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+
+    /// Returns a mutable reference to the image, copying the pixel
+    /// buffer first if it is shared with another clone.
+    pub fn make_mut(&mut self) -> &mut Image {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Returns the underlying image, copying the pixel buffer if it is
+    /// shared with another clone.
+    pub fn into_image(self) -> Image {
+        Arc::unwrap_or_clone(self.0)
+    }
+}
+
+impl Deref for SharedImage {
+    type Target = Image;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Image> for SharedImage {
+    fn from(image: Image) -> Self {
+        Self::new(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, Image, Point, Size};
+
+    use super::SharedImage;
+
+    #[test]
+    fn shallow_clone() {
+        let image = Image::color(
+            &Color::RED,
+            Size {
+                width: 4,
+                height: 3,
+            },
+        );
+        let original = SharedImage::new(image.clone());
+        let mut copy = original.shallow_clone();
+        assert!(original.is_shared());
+        assert_eq!(original.data.as_ptr(), copy.data.as_ptr());
+
+        copy.make_mut()
+            .set_pixel_color(Color::BLUE, Point { x: 1, y: 2 });
+
+        assert!(!original.is_shared());
+        assert_eq!(*original, image);
+        assert_eq!(copy.pixel_color(Point { x: 1, y: 2 }), Some(Color::BLUE));
+        assert_eq!(original.into_image(), image);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Image, Point, Rect, Size};
+use crate::{Color, Image, Point, Rect, Size};
 
 impl Image {
     /// Flips an image horizontally.
@@ -60,6 +60,74 @@ impl Image {
         new_image
     }
 
+    /// Scales up the image by separate integer multiples horizontally and
+    /// vertically. Each pixel is translated into a block of pixels that is
+    /// `x_factor` wide and `y_factor` tall, all having the same colour.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either factor is zero.
+    pub fn scaled_up_xy(&self, x_factor: u32, y_factor: u32) -> Image {
+        assert!(
+            x_factor > 0 && y_factor > 0,
+            "Scale factors must be greater than zero."
+        );
+        let new_size = Size {
+            width: self.size.width * x_factor,
+            height: self.size.height * y_factor,
+        };
+        let mut new_image = Image::empty(new_size);
+        let row_length = self.size.width as usize * 4;
+
+        for y in 0..self.size.height as usize {
+            let source_start = y * self.bytes_per_row as usize;
+            let source_row = &self.data[source_start..source_start + row_length];
+            let mut row = Vec::with_capacity(row_length * x_factor as usize);
+            for pixel in source_row.chunks_exact(4) {
+                for _ in 0..x_factor {
+                    row.extend_from_slice(pixel);
+                }
+            }
+            for repeat in 0..y_factor as usize {
+                let target_y = y * y_factor as usize + repeat;
+                let target_start = target_y * new_image.bytes_per_row as usize;
+                new_image.data[target_start..target_start + row.len()].copy_from_slice(&row);
+            }
+        }
+
+        new_image
+    }
+
+    /// Scales down the image by an integer factor by taking the top left
+    /// pixel of each `factor` x `factor` block, rather than averaging
+    /// them, which keeps pixel art crisp. A partial block on the right or
+    /// bottom edge still produces a pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the factor is zero.
+    pub fn scaled_down(&self, factor: u32) -> Image {
+        assert!(factor > 0, "The scale factor must be greater than zero.");
+        let new_size = Size {
+            width: self.size.width.div_ceil(factor),
+            height: self.size.height.div_ceil(factor),
+        };
+        let mut new_image = Image::empty(new_size);
+
+        for y in 0..new_size.height as usize {
+            let source_row = y * factor as usize * self.bytes_per_row as usize;
+            let target_row = y * new_image.bytes_per_row as usize;
+            for x in 0..new_size.width as usize {
+                let offset = source_row + x * factor as usize * 4;
+                let new_offset = target_row + x * 4;
+                new_image.data[new_offset..new_offset + 4]
+                    .copy_from_slice(&self.data[offset..offset + 4]);
+            }
+        }
+
+        new_image
+    }
+
     /// Resizes an image using the nearest neighbour algorithm.
     pub fn resize_nearest_neighbor(&mut self, new_size: Size<u32>) {
         let mut new_image = Image::empty(new_size);
@@ -88,9 +156,121 @@ impl Image {
         *self = new_image;
     }
 
+    /// Resizes an image using bilinear interpolation, which gives smoother
+    /// results than nearest neighbour when resizing photos. Colours are
+    /// weighted by alpha so that transparent pixels do not bleed into
+    /// their neighbours.
+    pub fn resize_bilinear(&mut self, new_size: Size<u32>) {
+        let mut new_image = Image::empty(new_size);
+        // There is nothing to sample from an empty image, so the result is
+        // left transparent.
+        if self.size.width == 0 || self.size.height == 0 {
+            *self = new_image;
+            return;
+        }
+
+        let x_scale = self.size.width as f32 / new_size.width as f32;
+        let y_scale = self.size.height as f32 / new_size.height as f32;
+        let max_x = self.size.width as f32 - 0.5;
+        let max_y = self.size.height as f32 - 0.5;
+
+        for y in 0..new_size.height {
+            for x in 0..new_size.width {
+                // Sampling at the centre of each new pixel, kept within the
+                // pixel centres at the edges so that the edges stay opaque.
+                let sample_x = ((x as f32 + 0.5) * x_scale).clamp(0.5, max_x);
+                let sample_y = ((y as f32 + 0.5) * y_scale).clamp(0.5, max_y);
+                let location = Point {
+                    x: sample_x,
+                    y: sample_y,
+                };
+                let Some(color) = self.sample_bilinear(location) else {
+                    continue;
+                };
+                let location = Point { x, y };
+                new_image.set_pixel_color(color, location);
+            }
+        }
+
+        *self = new_image;
+    }
+
+    /// Rotates the image by 90 degrees clockwise. Pixels are moved
+    /// exactly, and the width and height are swapped.
+    pub fn rotate_90_cw(&mut self) {
+        let height = self.size.height;
+        self.rotate_right_angle(
+            Size {
+                width: self.size.height,
+                height: self.size.width,
+            },
+            |x, y| Point {
+                x: y,
+                y: height - 1 - x,
+            },
+        );
+    }
+
+    /// Rotates the image by 90 degrees counterclockwise. Pixels are moved
+    /// exactly, and the width and height are swapped.
+    pub fn rotate_90_ccw(&mut self) {
+        let width = self.size.width;
+        self.rotate_right_angle(
+            Size {
+                width: self.size.height,
+                height: self.size.width,
+            },
+            |x, y| Point {
+                x: width - 1 - y,
+                y: x,
+            },
+        );
+    }
+
+    /// Rotates the image by 180 degrees. Pixels are moved exactly.
+    pub fn rotate_180(&mut self) {
+        let size = self.size;
+        self.rotate_right_angle(size, |x, y| Point {
+            x: size.width - 1 - x,
+            y: size.height - 1 - y,
+        });
+    }
+
+    /// Replaces the image with one of the new size, where each pixel is
+    /// copied from the location in the original returned by `source`.
+    fn rotate_right_angle<F>(&mut self, new_size: Size<u32>, source: F)
+    where
+        F: Fn(u32, u32) -> Point<u32>,
+    {
+        let mut new_image = Image::empty(new_size);
+        for y in 0..new_size.height {
+            for x in 0..new_size.width {
+                let location = source(x, y);
+                let offset = (location.y * self.bytes_per_row + location.x * 4) as usize;
+                let new_offset = (y * new_image.bytes_per_row + x * 4) as usize;
+                new_image.data[new_offset..new_offset + 4]
+                    .copy_from_slice(&self.data[offset..offset + 4]);
+            }
+        }
+        *self = new_image;
+    }
+
     /// Rotates the image using the nearest neighbour algorithm.
     /// Returns the offset for the new origin.
     pub fn rotate_nearest_neighbor(&mut self, angle: f32, center: Point<f32>) -> Point<i32> {
+        let (new_image, offset) = self.rotated(angle, center, Sampling::NearestNeighbor);
+        *self = new_image;
+        offset
+    }
+
+    /// Returns a copy of the image rotated about `center`, along with the
+    /// offset for the new origin. The original image is left unchanged.
+    pub fn rotated(
+        &self,
+        angle: f32,
+        center: Point<f32>,
+        sampling: Sampling,
+    ) -> (Image, Point<i32>) {
         let bounds = Rect {
             origin: Point::zero(),
             size: self.size.into(),
@@ -114,16 +294,107 @@ impl Image {
                 let rotated_location: Point<f32> = location.into();
                 let rotated_location = rotated_location + Point { x: 0.5, y: 0.5 };
                 let rotated_location = rotated_location.rotated(-angle, center);
-                let rotated_location = rotated_location.floored();
-                let Some(color) = self.pixel_color(rotated_location) else {
+                let color = match sampling {
+                    Sampling::NearestNeighbor => self.pixel_color(rotated_location.floored()),
+                    Sampling::Bilinear => self.sample_bilinear(rotated_location),
+                };
+                let Some(color) = color else {
                     continue;
                 };
                 new_image.set_pixel_color(color, location + offset.into());
             }
         }
 
-        *self = new_image;
+        (new_image, offset.into())
+    }
 
-        offset.into()
+    /// Samples the image at a point by interpolating between the four
+    /// nearest pixels, where pixel centres lie on half coordinates.
+    /// Returns `None` if none of the pixels are visible.
+    fn sample_bilinear(&self, point: Point<f32>) -> Option<Color> {
+        let x = point.x - 0.5;
+        let y = point.y - 0.5;
+        let min_x = x.floor();
+        let min_y = y.floor();
+        let x_fraction = x - min_x;
+        let y_fraction = y - min_y;
+        let min_x = min_x as i32;
+        let min_y = min_y as i32;
+
+        self.sample_premultiplied(&[
+            (
+                Point { x: min_x, y: min_y },
+                (1.0 - x_fraction) * (1.0 - y_fraction),
+            ),
+            (
+                Point {
+                    x: min_x + 1,
+                    y: min_y,
+                },
+                x_fraction * (1.0 - y_fraction),
+            ),
+            (
+                Point {
+                    x: min_x,
+                    y: min_y + 1,
+                },
+                (1.0 - x_fraction) * y_fraction,
+            ),
+            (
+                Point {
+                    x: min_x + 1,
+                    y: min_y + 1,
+                },
+                x_fraction * y_fraction,
+            ),
+        ])
     }
+
+    /// Returns the weighted sum of the pixels at the given locations.
+    /// The pixels are converted to premultiplied alpha before they are
+    /// combined and the result is converted back, so that the colours of
+    /// transparent pixels do not bleed into their neighbours and leave
+    /// dark fringes. Locations outside of the image are treated as
+    /// transparent. Returns `None` if the result is fully transparent.
+    ///
+    /// Every interpolating transformation should sample through this.
+    fn sample_premultiplied(&self, samples: &[(Point<i32>, f32)]) -> Option<Color> {
+        let mut totals = [0.0; 4];
+        for (location, weight) in samples {
+            // `pixel_color` allows locations on the far edges, which would
+            // read from the start of the next row.
+            if location.x >= self.size.width as i32 || location.y >= self.size.height as i32 {
+                continue;
+            }
+            let Some(color) = self.pixel_color(*location) else {
+                continue;
+            };
+            let alpha = color.alpha as f32 * weight;
+            totals[0] += color.red as f32 * alpha;
+            totals[1] += color.green as f32 * alpha;
+            totals[2] += color.blue as f32 * alpha;
+            totals[3] += alpha;
+        }
+
+        if totals[3] <= 0.0 {
+            return None;
+        }
+        let channel = |total: f32| (total / totals[3]).round().clamp(0.0, 255.0) as u8;
+        Some(Color {
+            red: channel(totals[0]),
+            green: channel(totals[1]),
+            blue: channel(totals[2]),
+            alpha: totals[3].round().clamp(0.0, 255.0) as u8,
+        })
+    }
+}
+
+/// The method used to sample pixels when transforming an image.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    /// Uses the colour of the nearest pixel. Best for pixel art.
+    #[default]
+    NearestNeighbor,
+    /// Interpolates between the four nearest pixels.
+    Bilinear,
 }
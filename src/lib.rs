@@ -15,7 +15,7 @@ pub use geometry::edge_insets::*;
 pub use geometry::point::*;
 pub use geometry::rect::*;
 pub use geometry::size::*;
-pub use image::Image;
+pub use image::{Image, ImageView};
 pub use mask::*;
 
 pub use ::image::ImageFormat;
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use graphics::{
-    composite::{Layer, Operation},
+    composite::{Group, Layer, Operation, OperationItem},
     *,
 };
 
@@ -642,3 +642,578 @@ fn test_compositing_with_offset() {
 
     assert!(color_image.appears_equal_to(&expected_image));
 }
+
+#[test]
+fn stacked_normal_layers_match_sequential_drawing() {
+    let canvas_size = Size {
+        width: 24,
+        height: 18,
+    };
+    let colors = [
+        0xff0000, 0x00ff00, 0x0000ff, 0xffff00, 0x00ffff, 0xff00ff, 0x5fcde4, 0xef5400, 0x99e550,
+        0x262b44,
+    ];
+    let images: Vec<Image> = colors
+        .iter()
+        .enumerate()
+        .map(|(index, color)| {
+            let mut color = Color::from_rgb_u32(*color);
+            color.alpha = 100 + index as u8 * 15;
+            Image::color(
+                &color,
+                Size {
+                    width: 9 + index as u32,
+                    height: 7 + index as u32,
+                },
+            )
+        })
+        .collect();
+    let layers: Vec<Layer> = images
+        .iter()
+        .enumerate()
+        .map(|(index, image)| {
+            let position = Point {
+                x: index as f32 * 2.4 - 4.0,
+                y: index as f32 * 1.6 - 3.0,
+            };
+            let mut layer = Layer::new(image, position);
+            layer.opacity = 1.0 - index as f32 * 0.05;
+            layer
+        })
+        .collect();
+
+    let mut expected_image = Image::empty(canvas_size);
+    for layer in layers.iter() {
+        composite::draw_layer_over_image(&mut expected_image, layer);
+    }
+
+    let operation = Operation::new(layers, canvas_size);
+    let result = composite(&operation);
+
+    assert_eq!(result.data, expected_image.data);
+}
+
+#[test]
+fn isolated_and_non_isolated_groups() {
+    let size = Size {
+        width: 1,
+        height: 1,
+    };
+    let backdrop = Image::color(&Color::RED, size);
+    let mut blue = Color::BLUE;
+    blue.alpha = 0x80;
+    let blue_image = Image::color(&blue, size);
+
+    let mut blue_layer = Layer::new(&blue_image, Point::zero());
+    blue_layer.blend_mode = BlendMode::Multiply;
+    let mut group = Group::new(vec![blue_layer]);
+
+    // The layer in an isolated group multiplies with a transparent
+    // backdrop, so the semi-transparent blue is drawn normally.
+    group.isolated = true;
+    let mut isolated_result = backdrop.clone();
+    composite::draw_group_over_image(&mut isolated_result, &group);
+    assert_eq!(isolated_result.data, vec![0x7f, 0x00, 0x80, 0xff]);
+
+    // The layer in a non-isolated group multiplies with the red backdrop,
+    // darkening it.
+    group.isolated = false;
+    let mut non_isolated_result = backdrop.clone();
+    composite::draw_group_over_image(&mut non_isolated_result, &group);
+    assert_eq!(non_isolated_result.data, vec![0x7f, 0x00, 0x00, 0xff]);
+
+    // The group’s own blend mode applies to its content either way.
+    let mut group = Group::new(vec![Layer::new(&blue_image, Point::zero())]);
+    group.blend_mode = BlendMode::Multiply;
+    for isolated in [true, false] {
+        group.isolated = isolated;
+        let mut result = backdrop.clone();
+        composite::draw_group_over_image(&mut result, &group);
+        assert_eq!(result.data, vec![0x7f, 0x00, 0x00, 0xff]);
+    }
+}
+
+#[test]
+fn composite_groups() {
+    let canvas_size = Size {
+        width: 20,
+        height: 16,
+    };
+    let background = Image::open("tests/images/avatar.png").unwrap();
+    let blue_image = Image::color(
+        &Color::BLUE.with_alpha(0x80),
+        Size {
+            width: 8,
+            height: 6,
+        },
+    );
+    let mut multiply_layer = Layer::new(&blue_image, Point { x: 3.0, y: 4.0 });
+    multiply_layer.blend_mode = BlendMode::Multiply;
+    let mut group = Group::new(vec![
+        multiply_layer,
+        Layer::new(&blue_image, Point { x: 9.0, y: 7.0 }),
+    ]);
+    group.blend_mode = BlendMode::Screen;
+    group.opacity = 0.75;
+
+    for isolated in [true, false] {
+        group.isolated = isolated;
+        let operation = Operation::with_items(
+            vec![
+                Layer::new(&background, Point::zero()).into(),
+                group.clone().into(),
+                Layer::new(&blue_image, Point { x: 12.0, y: 2.0 }).into(),
+            ],
+            canvas_size,
+        );
+        assert_eq!(operation.layers().count(), 2);
+        let result = composite(&operation);
+
+        let mut expected = Image::empty(canvas_size);
+        composite::draw_layer_over_image(&mut expected, &Layer::new(&background, Point::zero()));
+        composite::draw_group_over_image(&mut expected, &group);
+        composite::draw_layer_over_image(
+            &mut expected,
+            &Layer::new(&blue_image, Point { x: 12.0, y: 2.0 }),
+        );
+        assert_eq!(result, expected);
+
+        let tile = Rect::new(5, 5, 10, 8);
+        let output = composite::composite_tile(&operation, tile);
+        let mut expected = result.clone();
+        expected
+            .crop_with_offset(tile.size.into(), tile.origin)
+            .unwrap();
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn layer_clip_rect() {
+    let canvas_size = Size {
+        width: 6,
+        height: 5,
+    };
+    let red_image = Image::color(&Color::RED, canvas_size);
+    let blue_image = Image::color(&Color::BLUE, canvas_size);
+    let clip_rect = Rect::new(2, 1, 3, 2);
+
+    let mut clipped_layer = Layer::new(&blue_image, Point::zero());
+    clipped_layer.clip_rect = Some(clip_rect);
+
+    // Both the batched and single layer compositing paths respect the clip.
+    let mut single_layer_result = red_image.clone();
+    composite::draw_layer_over_image(&mut single_layer_result, &clipped_layer);
+    let operation = Operation::new(
+        vec![Layer::new(&red_image, Point::zero()), clipped_layer],
+        canvas_size,
+    );
+    let batched_result = composite(&operation);
+
+    for result in [single_layer_result, batched_result] {
+        for y in 0..canvas_size.height as i32 {
+            for x in 0..canvas_size.width as i32 {
+                let location = Point { x, y };
+                let expected_color = if (2..5).contains(&x) && (1..3).contains(&y) {
+                    Color::BLUE
+                } else {
+                    Color::RED
+                };
+                assert_eq!(result.pixel_color(location), Some(expected_color));
+            }
+        }
+    }
+}
+
+#[test]
+fn linear_blending() {
+    let size = Size {
+        width: 1,
+        height: 1,
+    };
+    let black_image = Image::color(&Color::BLACK, size);
+    let white_image = Image::color(&Color::WHITE, size);
+    let layers = || {
+        let mut white_layer = Layer::new(&white_image, Point::zero());
+        white_layer.opacity = 0.5;
+        vec![Layer::new(&black_image, Point::zero()), white_layer]
+    };
+
+    // Blending in sRGB space puts the midtone halfway between the
+    // stored values.
+    let operation = Operation::new(layers(), size);
+    let result = composite(&operation);
+    assert_eq!(result.data, vec![0x80, 0x80, 0x80, 0xff]);
+
+    // Blending in linear light puts the midtone halfway between the
+    // light intensities, which is brighter once converted back to sRGB.
+    let mut operation = Operation::new(layers(), size);
+    operation.linear_blending = true;
+    let result = composite(&operation);
+    assert_eq!(result.data, vec![0xbc, 0xbc, 0xbc, 0xff]);
+}
+
+#[test]
+fn feathered_selection() {
+    let canvas_size = Size {
+        width: 20,
+        height: 20,
+    };
+    let mut image = Image::color(&Color::RED, canvas_size);
+    let blue_image = Image::color(&Color::BLUE, canvas_size);
+    let layer = Layer::new(&blue_image, Point::zero());
+    let selection = Rect::new(2, 2, 16, 16);
+
+    composite::draw_layer_over_image_feathered(&mut image, &layer, selection, 4.0);
+
+    // Outside of the selection is untouched.
+    assert_eq!(image.pixel_color(Point { x: 1, y: 10 }), Some(Color::RED));
+    assert_eq!(image.pixel_color(Point { x: 18, y: 10 }), Some(Color::RED));
+    // The centre is fully blended.
+    assert_eq!(image.pixel_color(Point { x: 10, y: 10 }), Some(Color::BLUE));
+    assert_eq!(image.pixel_color(Point { x: 6, y: 10 }), Some(Color::BLUE));
+    // The edges are partially blended, increasing towards the centre.
+    let edge_color = image.pixel_color(Point { x: 2, y: 10 }).unwrap();
+    let inner_edge_color = image.pixel_color(Point { x: 4, y: 10 }).unwrap();
+    assert!(edge_color.blue > 0 && edge_color.red > 0);
+    assert!(inner_edge_color.blue > edge_color.blue);
+    assert!(inner_edge_color.blue < 0xff);
+    // Corners fade out based on the nearest edge.
+    assert_eq!(
+        image.pixel_color(Point { x: 2, y: 2 }),
+        image.pixel_color(Point { x: 2, y: 10 })
+    );
+}
+
+#[test]
+fn f64_layer_position() {
+    // At this magnitude `f32` values are spaced half a pixel apart, so the
+    // position is stored as 4,194,304.5 and rounds to the wrong pixel.
+    let x = 4_194_304.4;
+    let canvas_size = Size {
+        width: 4_194_308,
+        height: 1,
+    };
+    let red_image = Image::color(
+        &Color::RED,
+        Size {
+            width: 1,
+            height: 1,
+        },
+    );
+
+    let mut layer = Layer::new(
+        &red_image,
+        Point {
+            x: x as f32,
+            y: 0.0,
+        },
+    );
+    assert_eq!(layer.rounded_position(), Point { x: 4_194_305, y: 0 });
+
+    layer.set_position_f64(Point { x, y: 0.0 });
+    assert_eq!(layer.rounded_position(), Point { x: 4_194_304, y: 0 });
+    assert_eq!(layer.position_f64(), Some(Point { x, y: 0.0 }));
+
+    let mut image = Image::empty(canvas_size);
+    composite::draw_layer_over_image(&mut image, &layer);
+    assert_eq!(
+        image.pixel_color(Point { x: 4_194_304, y: 0 }),
+        Some(Color::RED)
+    );
+    assert_eq!(
+        image.pixel_color(Point { x: 4_194_305, y: 0 }),
+        Some(Color::CLEAR)
+    );
+
+    // Assigning a new single precision position replaces the double
+    // precision one.
+    let mut moved_layer = layer.clone();
+    moved_layer.position = Point { x: 12.0, y: 3.0 };
+    assert_eq!(moved_layer.position_f64(), None);
+    assert_eq!(moved_layer.rounded_position(), Point { x: 12, y: 3 });
+}
+
+#[test]
+fn addition_preserving_hue() {
+    let size = Size {
+        width: 1,
+        height: 1,
+    };
+    let base_image = Image::color(&Color::from_rgb_u32(0xc06020), size);
+    let blend_image = Image::color(&Color::from_rgb_u32(0x804010), size);
+    let layers = || {
+        let mut blend_layer = Layer::new(&blend_image, Point::zero());
+        blend_layer.blend_mode = BlendMode::Addition;
+        vec![Layer::new(&base_image, Point::zero()), blend_layer]
+    };
+
+    // Clipping each channel turns the orange into a yellower colour.
+    let operation = Operation::new(layers(), size);
+    let result = composite(&operation);
+    assert_eq!(result.data, vec![0xff, 0xa0, 0x30, 0xff]);
+
+    // Scaling the channels keeps the 20:10:3 ratio of the sum.
+    let mut operation = Operation::new(layers(), size);
+    operation.preserve_hue_on_clamp = true;
+    let result = composite(&operation);
+    assert_eq!(result.data, vec![0xff, 0x80, 0x26, 0xff]);
+}
+
+#[test]
+fn layer_canvas_bounds() {
+    let image = Image::empty(Size {
+        width: 5,
+        height: 5,
+    });
+    let layer = Layer::new(&image, Point { x: 10.4, y: -2.6 });
+    assert_eq!(layer.canvas_bounds(), Rect::new(10, -3, 5, 5));
+
+    let other_layer = Layer::new(&image, Point { x: 2.5, y: 1.0 });
+    let dirty_region = layer.canvas_bounds().union(&other_layer.canvas_bounds());
+    assert_eq!(dirty_region, Rect::new(3, -3, 12, 9));
+}
+
+#[test]
+fn tiled_layer() {
+    let canvas_size = Size {
+        width: 5,
+        height: 2,
+    };
+    let background = Image::color(&Color::WHITE, canvas_size);
+    let mut pattern = Image::empty(Size {
+        width: 2,
+        height: 1,
+    });
+    pattern.set_pixel_color(Color::RED.with_alpha(0x80), Point { x: 0, y: 0 });
+
+    let mut layer = Layer::new(&pattern, Point { x: 1.0, y: 0.0 });
+    layer.tiled = true;
+    let operation = Operation::new(
+        vec![Layer::new(&background, Point::zero()), layer],
+        canvas_size,
+    );
+    let output = composite(&operation);
+
+    let tinted = output.pixel_color(Point { x: 1, y: 0 }).unwrap();
+    assert!(tinted.is_within_tolerance_of(&Color::from_rgb_u32(0xff8080), 1));
+    for y in 0..2 {
+        for x in 0..5 {
+            let color = output.pixel_color(Point { x, y }).unwrap();
+            if x % 2 == 1 {
+                assert_eq!(color, tinted);
+            } else {
+                assert_eq!(color, Color::WHITE);
+            }
+        }
+    }
+}
+
+#[test]
+fn layer_is_hit() {
+    let mut image = Image::empty(Size {
+        width: 3,
+        height: 3,
+    });
+    image.set_pixel_color(Color::RED, Point { x: 1, y: 1 });
+    let layer = Layer::new(&image, Point { x: 10.0, y: -2.0 });
+
+    assert!(layer.is_hit(Point { x: 11, y: -1 }, 0xff));
+    assert!(!layer.is_hit(Point { x: 10, y: -2 }, 1));
+    assert!(!layer.is_hit(Point { x: 1, y: 1 }, 1));
+}
+
+#[test]
+fn wrapped_layer() {
+    let canvas_size = Size {
+        width: 5,
+        height: 3,
+    };
+    let mut image = Image::color(&Color::WHITE, canvas_size);
+    let brush = Image::color(
+        &Color::RED,
+        Size {
+            width: 3,
+            height: 2,
+        },
+    );
+    let layer = Layer::new(&brush, Point { x: 3.0, y: 2.0 });
+
+    composite::draw_layer_wrapped(&mut image, &layer);
+
+    for y in 0..3 {
+        for x in 0..5 {
+            let expected = if [3, 4, 0].contains(&x) && [2, 0].contains(&y) {
+                Color::RED
+            } else {
+                Color::WHITE
+            };
+            assert_eq!(
+                image.pixel_color(Point { x, y }),
+                Some(expected),
+                "x: {x}, y: {y}"
+            );
+        }
+    }
+}
+
+#[test]
+fn render_key() {
+    let canvas_size = Size {
+        width: 4,
+        height: 4,
+    };
+    let background = Image::color(&Color::WHITE, canvas_size);
+    let red_image = Image::color(
+        &Color::RED,
+        Size {
+            width: 2,
+            height: 2,
+        },
+    );
+    let red_copy = red_image.clone();
+    let make_layer = |image, opacity| {
+        let mut layer = Layer::new(image, Point { x: 1.0, y: 1.0 });
+        layer.opacity = opacity;
+        layer
+    };
+    let make_operation = |layer| {
+        Operation::new(
+            vec![Layer::new(&background, Point::zero()), layer],
+            canvas_size,
+        )
+    };
+
+    let key = make_operation(make_layer(&red_image, 0.5)).render_key();
+    assert_eq!(make_operation(make_layer(&red_copy, 0.5)).render_key(), key);
+    assert_ne!(
+        make_operation(make_layer(&red_image, 0.6)).render_key(),
+        key
+    );
+
+    let mut moved = make_layer(&red_image, 0.5);
+    moved.position = Point { x: 1.2, y: 1.0 };
+    assert_eq!(make_operation(moved.clone()).render_key(), key);
+    moved.position = Point { x: 2.0, y: 1.0 };
+    assert_ne!(make_operation(moved).render_key(), key);
+
+    let mut blended = make_operation(make_layer(&red_image, 0.5));
+    blended.layers_mut().last().unwrap().blend_mode = BlendMode::Multiply;
+    assert_eq!(blended.layers().count(), 2);
+    assert_ne!(blended.render_key(), key);
+
+    // Groups and their properties are part of the key.
+    let make_grouped_operation = |group| {
+        Operation::with_items(
+            vec![
+                OperationItem::Layer(Layer::new(&background, Point::zero())),
+                OperationItem::Group(group),
+            ],
+            canvas_size,
+        )
+    };
+    let mut group = Group::new(vec![make_layer(&red_image, 0.5)]);
+    let group_key = make_grouped_operation(group.clone()).render_key();
+    assert_ne!(group_key, key);
+    group.isolated = true;
+    assert_ne!(make_grouped_operation(group).render_key(), group_key);
+}
+
+#[test]
+fn composite_tile() {
+    let canvas_size = Size {
+        width: 40,
+        height: 30,
+    };
+    let background = Image::open("tests/images/avatar.png").unwrap();
+    let red_image = Image::color(
+        &Color::RED.with_alpha(0x80),
+        Size {
+            width: 12,
+            height: 9,
+        },
+    );
+    let mut pattern = Image::empty(Size {
+        width: 3,
+        height: 2,
+    });
+    pattern.set_pixel_color(Color::BLUE, Point { x: 1, y: 0 });
+
+    let mut red_layer = Layer::new(&red_image, Point { x: 9.6, y: 5.2 });
+    red_layer.blend_mode = BlendMode::Multiply;
+    let mut pattern_layer = Layer::new(&pattern, Point { x: 1.0, y: 1.0 });
+    pattern_layer.tiled = true;
+    pattern_layer.clip_rect = Some(Rect::new(5, 5, 20, 20));
+    let overhanging_layer = Layer::new(&red_image, Point { x: 30.0, y: 25.0 });
+    let operation = Operation::new(
+        vec![
+            Layer::new(&background, Point { x: -3.0, y: 2.0 }),
+            red_layer,
+            pattern_layer,
+            overhanging_layer,
+        ],
+        canvas_size,
+    );
+    let full = composite(&operation);
+
+    for tile in [
+        Rect::new(0, 0, 16, 16),
+        Rect::new(16, 8, 16, 16),
+        Rect::new(32, 16, 16, 16),
+    ] {
+        let output = composite::composite_tile(&operation, tile);
+        assert_eq!(output.size, tile.size.into());
+        let mut expected = full.clone();
+        expected
+            .crop_with_offset(tile.size.into(), tile.origin)
+            .unwrap();
+        assert_eq!(output, expected, "{tile:?}");
+    }
+}
+
+#[test]
+fn masked_channels() {
+    let canvas_size = Size {
+        width: 4,
+        height: 4,
+    };
+    let base_color = Color::from_rgba_u32(0x20406080);
+    let mut image = Image::color(&base_color, canvas_size);
+    let layer_image = Image::color(
+        &Color::from_rgb_u32(0xa0c0e0),
+        Size {
+            width: 2,
+            height: 2,
+        },
+    );
+    let layer = Layer::new(&layer_image, Point { x: 1.0, y: 1.0 });
+
+    let mut expected = image.clone();
+    composite::draw_layer_over_image(&mut expected, &layer);
+    composite::draw_layer_over_image_masked_channels(
+        &mut image,
+        &layer,
+        [false, false, true, false],
+    );
+
+    let blended = expected.pixel_color(Point { x: 1, y: 1 }).unwrap();
+    assert_ne!(blended.blue, base_color.blue);
+    let blue_only = Color {
+        blue: blended.blue,
+        ..base_color.clone()
+    };
+    for y in 0..4 {
+        for x in 0..4 {
+            let expected = if (1..3).contains(&x) && (1..3).contains(&y) {
+                blue_only.clone()
+            } else {
+                base_color.clone()
+            };
+            assert_eq!(image.pixel_color(Point { x, y }), Some(expected));
+        }
+    }
+
+    let mut all_channels = Image::color(&base_color, canvas_size);
+    composite::draw_layer_over_image_masked_channels(&mut all_channels, &layer, [true; 4]);
+    assert_eq!(all_channels, expected);
+}
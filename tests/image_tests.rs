@@ -7,7 +7,7 @@ mod tests {
     };
 
     use flate2::{bufread::ZlibDecoder, write::ZlibEncoder, Compression};
-    use graphics::{Color, Image, Point, Rect, Size};
+    use graphics::{image::transformation::Sampling, Color, EdgeInsets, Image, Point, Rect, Size};
     use image::{ColorType, ImageFormat};
     use tiff::encoder::{colortype::RGBA8, compression::Lzw, *};
 
@@ -117,6 +117,71 @@ mod tests {
         panic!()
     }
 
+    #[test]
+    fn test_premultiplied_bytes() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/small-with-alpha.png");
+        let image = Image::open(path).unwrap();
+        let original_image = image.clone();
+
+        let result = image.premultiplied_bytes();
+
+        let mut expected_bytes = Vec::new();
+        for y in 0..image.size.height as i32 {
+            for x in 0..image.size.width as i32 {
+                let color = image.pixel_color(Point { x, y }).unwrap();
+                let alpha = color.alpha as f32 / 255.0;
+                expected_bytes.push((color.red as f32 * alpha).round() as u8);
+                expected_bytes.push((color.green as f32 * alpha).round() as u8);
+                expected_bytes.push((color.blue as f32 * alpha).round() as u8);
+                expected_bytes.push(color.alpha);
+            }
+        }
+
+        assert_eq!(result, expected_bytes);
+        assert_eq!(image, original_image);
+    }
+
+    #[test]
+    fn test_difference_overlay() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/avatar.png");
+        let image = Image::open(path).unwrap();
+
+        let mut other_image = image.clone();
+        let location = Point { x: 4, y: 6 };
+        other_image.set_pixel_color(Color::from_rgb_u32(0x123456), location);
+
+        let overlay = image
+            .difference_overlay(&other_image, &Color::MAGENTA)
+            .unwrap();
+
+        let original_color = image.pixel_color(location.into()).unwrap();
+        let tinted_color = overlay.pixel_color(location.into()).unwrap();
+        assert_ne!(tinted_color, original_color);
+        assert_eq!(
+            tinted_color.red,
+            (original_color.red as u16 + 0xff).div_ceil(2) as u8
+        );
+        assert_eq!(
+            tinted_color.green,
+            (original_color.green as u16).div_ceil(2) as u8
+        );
+
+        // Every other pixel is untouched.
+        let mut expected_image = overlay.clone();
+        expected_image.set_pixel_color(original_color, location);
+        assert_eq!(expected_image, image);
+
+        let small_image = Image::empty(Size {
+            width: 2,
+            height: 2,
+        });
+        assert!(image
+            .difference_overlay(&small_image, &Color::MAGENTA)
+            .is_err());
+    }
+
     #[test]
     fn test_trim() {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -149,6 +214,62 @@ mod tests {
         assert_eq!(trimmed_rect, Rect::new(0, 0, 20, 21));
     }
 
+    #[test]
+    fn test_smart_trim() {
+        // A screenshot-like image with a grey border that has a little noise.
+        let mut image = Image::color(
+            &Color::from_rgb_u32(0x808080),
+            Size {
+                width: 10,
+                height: 8,
+            },
+        );
+        image.set_pixel_color(Color::from_rgb_u32(0x828180), Point { x: 1, y: 1 });
+        let content = Image::color(
+            &Color::from_rgb_u32(0x2060c0),
+            Size {
+                width: 4,
+                height: 3,
+            },
+        );
+        image.draw_image_over(&content, Point { x: 3, y: 2 });
+
+        let trimmed_rect = image.smart_trim(4).unwrap();
+
+        assert_eq!(trimmed_rect, Rect::new(3, 2, 4, 3));
+        assert!(image.appears_equal_to(&content));
+    }
+
+    #[test]
+    fn test_empty_aligned() {
+        let size = Size {
+            width: 30,
+            height: 5,
+        };
+        let mut image = Image::empty_aligned(size, 256);
+        assert_eq!(image.bytes_per_row, 256);
+        assert!(image.bytes_per_row.is_multiple_of(256));
+
+        image.set_pixel_color(Color::RED, Point { x: 29, y: 4 });
+        assert_eq!(image.pixel_color(Point { x: 29, y: 4 }), Some(Color::RED));
+
+        let color_image = Image::color(
+            &Color::BLUE,
+            Size {
+                width: 2,
+                height: 2,
+            },
+        );
+        image.draw_image_over(&color_image, Point { x: 28, y: 0 });
+        assert_eq!(image.pixel_color(Point { x: 29, y: 1 }), Some(Color::BLUE));
+        assert_eq!(image.pixel_color(Point { x: 27, y: 1 }), Some(Color::CLEAR));
+
+        let mut expected_image = Image::empty(size);
+        expected_image.draw_image_over(&color_image, Point { x: 28, y: 0 });
+        expected_image.set_pixel_color(Color::RED, Point { x: 29, y: 4 });
+        assert!(image.appears_equal_to(&expected_image));
+    }
+
     #[test]
     fn test_draw_image_over() {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -381,4 +502,726 @@ mod tests {
         image.save("/tmp/3x2-rotated.png").unwrap();
         assert!(image.appears_equal_to(&expected_image));
     }
+
+    #[test]
+    fn test_rotated_copy() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/tv.png");
+        let image = Image::open(path).unwrap();
+        let original = image.clone();
+
+        let midpoint = Point {
+            x: image.size.width as f32 * 0.5,
+            y: image.size.height as f32 * 0.5,
+        };
+        let angle = std::f32::consts::PI * 0.2;
+        let (rotated, offset) = image.rotated(angle, midpoint, Sampling::NearestNeighbor);
+        assert_eq!(image, original);
+
+        let mut expected_image = image.clone();
+        let expected_offset = expected_image.rotate_nearest_neighbor(angle, midpoint);
+        assert_eq!(rotated, expected_image);
+        assert_eq!(offset, expected_offset);
+
+        let (rotated, _) = image.rotated(0.0, midpoint, Sampling::Bilinear);
+        assert_eq!(image, original);
+        assert_eq!(rotated, image);
+    }
+
+    #[test]
+    fn test_transparent_edges() {
+        let mut image = Image::empty(Size {
+            width: 4,
+            height: 3,
+        });
+        assert_eq!(image.transparent_edges(), (true, true, true, true));
+
+        image.set_pixel_color(Color::RED, Point { x: 0, y: 1 });
+        image.set_pixel_color(Color::RED, Point { x: 1, y: 1 });
+        assert_eq!(image.transparent_edges(), (true, true, true, false));
+
+        image.set_pixel_color(Color::RED, Point { x: 3, y: 2 });
+        assert_eq!(image.transparent_edges(), (true, false, false, false));
+
+        for (width, height) in [(0, 3), (4, 0), (0, 0)] {
+            let image = Image::empty(Size { width, height });
+            assert_eq!(image.transparent_edges(), (true, true, true, true));
+        }
+    }
+
+    #[test]
+    fn test_resize_bilinear() {
+        let mut image = Image::color(
+            &Color::WHITE,
+            Size {
+                width: 4,
+                height: 4,
+            },
+        );
+        for y in 0..4 {
+            for x in 0..4 {
+                if (x + y) % 2 == 1 {
+                    image.set_pixel_color(Color::BLACK, Point { x, y });
+                }
+            }
+        }
+
+        let new_size = Size {
+            width: 2,
+            height: 2,
+        };
+        image.resize_bilinear(new_size);
+
+        let grey = Color::from_rgb_u32(0x808080);
+        assert_eq!(image, Image::color(&grey, new_size));
+
+        for (width, height) in [(0, 3), (4, 0)] {
+            let mut image = Image::empty(Size { width, height });
+            image.resize_bilinear(new_size);
+            assert_eq!(image, Image::empty(new_size));
+        }
+        image.resize_bilinear(Size {
+            width: 0,
+            height: 2,
+        });
+        assert_eq!(image.data.len(), 0);
+    }
+
+    #[test]
+    fn test_resize_bilinear_keeps_edge_colors() {
+        let mut image = Image::empty(Size {
+            width: 4,
+            height: 4,
+        });
+        for y in 0..4 {
+            for x in 0..2 {
+                image.set_pixel_color(Color::WHITE, Point { x, y });
+            }
+        }
+
+        image.resize_bilinear(Size {
+            width: 3,
+            height: 2,
+        });
+
+        for y in 0..2 {
+            assert_eq!(image.pixel_color(Point { x: 0, y }), Some(Color::WHITE));
+            let edge = image.pixel_color(Point { x: 1, y }).unwrap();
+            assert_eq!(edge, Color::WHITE.with_alpha(0x80));
+            assert_eq!(image.pixel_color(Point { x: 2, y }), Some(Color::CLEAR));
+        }
+    }
+
+    #[test]
+    fn test_scaled_up_xy() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/2x2.png");
+        let image = Image::open(path).unwrap();
+
+        let scaled_image = image.scaled_up_xy(3, 2);
+
+        assert_eq!(
+            scaled_image.size,
+            Size {
+                width: 6,
+                height: 4
+            }
+        );
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let expected = image.pixel_color(Point { x, y });
+            for (block_x, block_y) in [(0, 0), (2, 0), (0, 1), (2, 1)] {
+                let location = Point {
+                    x: x * 3 + block_x,
+                    y: y * 2 + block_y,
+                };
+                assert_eq!(scaled_image.pixel_color(location), expected);
+            }
+        }
+        assert_eq!(image.scaled_up_xy(2, 2), image.scaled_up(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_scaled_up_xy_by_zero() {
+        let image = Image::color(
+            &Color::RED,
+            Size {
+                width: 2,
+                height: 2,
+            },
+        );
+        image.scaled_up_xy(0, 2);
+    }
+
+    #[test]
+    fn test_right_angle_rotations() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/3x2.png");
+        let original = Image::open(path).unwrap();
+        let pixel = |image: &Image, x: i32, y: i32| image.pixel_color(Point { x, y }).unwrap();
+
+        let mut image = original.clone();
+        image.rotate_90_cw();
+        let rotated_size = Size {
+            width: 2,
+            height: 3,
+        };
+        let mut expected_image = Image::empty(rotated_size);
+        for (x, y, source_x, source_y) in [
+            (0, 0, 0, 1),
+            (1, 0, 0, 0),
+            (0, 1, 1, 1),
+            (1, 1, 1, 0),
+            (0, 2, 2, 1),
+            (1, 2, 2, 0),
+        ] {
+            let color = pixel(&original, source_x, source_y);
+            expected_image.set_pixel_color(color, Point { x, y });
+        }
+        assert_eq!(image, expected_image);
+
+        image.rotate_90_ccw();
+        assert_eq!(image, original);
+
+        image.rotate_180();
+        assert_eq!(pixel(&image, 0, 0), pixel(&original, 2, 1));
+        assert_eq!(pixel(&image, 2, 0), pixel(&original, 0, 1));
+        assert_eq!(pixel(&image, 1, 1), pixel(&original, 1, 0));
+        image.rotate_180();
+        assert_eq!(image, original);
+
+        for _ in 0..4 {
+            image.rotate_90_cw();
+        }
+        assert_eq!(image, original);
+    }
+
+    #[test]
+    fn test_trim_matte() {
+        let mut image = Image::color(
+            &Color::WHITE,
+            Size {
+                width: 9,
+                height: 9,
+            },
+        );
+        // A red square with an anti-aliased edge, half blended with white.
+        for y in 2..7 {
+            for x in 2..7 {
+                image.set_pixel_color(Color::from_rgb_u32(0xff8080), Point { x, y });
+            }
+        }
+        for y in 3..6 {
+            for x in 3..6 {
+                image.set_pixel_color(Color::RED, Point { x, y });
+            }
+        }
+
+        let mut trimmed_image = image.clone();
+        let rect = trimmed_image.trim_matte(&Color::WHITE, 2, false).unwrap();
+        assert_eq!(rect, Rect::new(2, 2, 5, 5));
+        assert_eq!(
+            trimmed_image.pixel_color(Point { x: 0, y: 0 }),
+            Some(Color::from_rgb_u32(0xff8080))
+        );
+
+        let rect = image.trim_matte(&Color::WHITE, 2, true).unwrap();
+        assert_eq!(rect, Rect::new(2, 2, 5, 5));
+        let edge = image.pixel_color(Point { x: 0, y: 2 }).unwrap();
+        assert!(edge.is_within_tolerance_of(&Color::RED.with_alpha(0x7f), 1));
+        assert_eq!(image.pixel_color(Point { x: 2, y: 2 }), Some(Color::RED));
+    }
+
+    #[test]
+    fn test_trim_matte_enclosed_matte() {
+        let mut image = Image::color(
+            &Color::WHITE,
+            Size {
+                width: 9,
+                height: 9,
+            },
+        );
+        // A red square with a matte coloured hole in the middle.
+        for y in 2..7 {
+            for x in 2..7 {
+                image.set_pixel_color(Color::RED, Point { x, y });
+            }
+        }
+        image.set_pixel_color(Color::WHITE, Point { x: 4, y: 4 });
+
+        let rect = image.trim_matte(&Color::WHITE, 2, true).unwrap();
+        assert_eq!(rect, Rect::new(2, 2, 5, 5));
+        assert_eq!(image.pixel_color(Point { x: 2, y: 2 }), Some(Color::WHITE));
+        assert_eq!(image.pixel_color(Point { x: 1, y: 2 }), Some(Color::RED));
+        assert_eq!(image.pixel_color(Point { x: 0, y: 0 }), Some(Color::RED));
+    }
+
+    #[test]
+    fn test_view() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/avatar.png");
+        let image = Image::open(path).unwrap();
+
+        let region = Rect::new(3, 5, 4, 2);
+        let view = image.view(region);
+
+        assert_eq!(
+            view.size(),
+            Size {
+                width: 4,
+                height: 2
+            }
+        );
+        assert_eq!(
+            view.pixel_color(Point { x: 0, y: 0 }),
+            image.pixel_color(Point { x: 3, y: 5 })
+        );
+        assert_eq!(
+            view.pixel_color(Point { x: 3, y: 1 }),
+            image.pixel_color(Point { x: 6, y: 6 })
+        );
+        assert_eq!(view.pixel_color(Point { x: 4, y: 0 }), None);
+        assert_eq!(view.pixel_color(Point { x: -1, y: 0 }), None);
+
+        let subimage = image.subimage(region).unwrap();
+        let pixels: Vec<(Point<i32>, Color)> = view.pixels().collect();
+        assert_eq!(pixels.len(), 8);
+        for (location, color) in pixels {
+            assert_eq!(subimage.pixel_color(location), Some(color));
+        }
+
+        let clipped_view = image.view(Rect::new(-2, -2, 4, 4));
+        assert_eq!(clipped_view.rect(), Rect::new(0, 0, 2, 2));
+    }
+
+    #[test]
+    fn test_crop_with_negative_offset() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/3x2.png");
+        let original = Image::open(path).unwrap();
+        let pixel = |image: &Image, x: i32, y: i32| image.pixel_color(Point { x, y }).unwrap();
+        let size = Size {
+            width: 3,
+            height: 2,
+        };
+
+        // Negative in the x axis only.
+        let mut image = original.clone();
+        image.crop_with_offset(size, Point { x: -1, y: 0 }).unwrap();
+        assert_eq!(image.size, size);
+        assert_eq!(pixel(&image, 0, 0), Color::CLEAR);
+        assert_eq!(pixel(&image, 0, 1), Color::CLEAR);
+        assert_eq!(pixel(&image, 1, 0), pixel(&original, 0, 0));
+        assert_eq!(pixel(&image, 2, 1), pixel(&original, 1, 1));
+
+        // Negative in both axes.
+        let mut image = original.clone();
+        image
+            .crop_with_offset(size, Point { x: -2, y: -1 })
+            .unwrap();
+        assert_eq!(pixel(&image, 2, 1), pixel(&original, 0, 0));
+        assert_eq!(pixel(&image, 1, 1), Color::CLEAR);
+        assert_eq!(pixel(&image, 2, 0), Color::CLEAR);
+
+        // Larger than the source, with the source in the middle.
+        let mut image = original.clone();
+        let larger_size = Size {
+            width: 5,
+            height: 4,
+        };
+        image
+            .crop_with_offset(larger_size, Point { x: -1, y: -1 })
+            .unwrap();
+        assert_eq!(image.size, larger_size);
+        assert_eq!(image.subimage(Rect::new(1, 1, 3, 2)).unwrap(), original);
+        assert_eq!(pixel(&image, 0, 0), Color::CLEAR);
+        assert_eq!(pixel(&image, 4, 3), Color::CLEAR);
+
+        // Positive offsets past the far edge.
+        let mut image = original.clone();
+        image.crop_with_offset(size, Point { x: 2, y: 1 }).unwrap();
+        assert_eq!(pixel(&image, 0, 0), pixel(&original, 2, 1));
+        assert_eq!(pixel(&image, 1, 0), Color::CLEAR);
+        assert_eq!(pixel(&image, 0, 1), Color::CLEAR);
+    }
+
+    #[test]
+    fn test_padded() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/images/2x2.png");
+        let image = Image::open(path).unwrap();
+
+        let padded_image = image.padded(EdgeInsets::all(1));
+
+        assert_eq!(
+            padded_image.size,
+            Size {
+                width: 4,
+                height: 4
+            }
+        );
+        assert_eq!(padded_image.subimage(Rect::new(1, 1, 2, 2)).unwrap(), image);
+        for y in 0..4 {
+            for x in 0..4 {
+                if (1..3).contains(&x) && (1..3).contains(&y) {
+                    continue;
+                }
+                assert_eq!(padded_image.pixel_color(Point { x, y }), Some(Color::CLEAR));
+            }
+        }
+
+        let padded_image = image.padded(EdgeInsets::new(0, 2, 1, 0));
+        assert_eq!(padded_image.subimage(Rect::new(2, 0, 2, 2)).unwrap(), image);
+        assert_eq!(image.padded(EdgeInsets::zero()), image);
+    }
+
+    #[test]
+    fn test_is_hit() {
+        let mut image = Image::empty(Size {
+            width: 3,
+            height: 3,
+        });
+        image.set_pixel_color(Color::RED, Point { x: 1, y: 1 });
+        image.set_pixel_color(Color::RED.with_alpha(0x40), Point { x: 2, y: 1 });
+
+        assert!(image.is_hit(Point { x: 1, y: 1 }, 0x80));
+        assert!(!image.is_hit(Point { x: 2, y: 1 }, 0x80));
+        assert!(image.is_hit(Point { x: 2, y: 1 }, 0x40));
+        assert!(!image.is_hit(Point { x: 0, y: 0 }, 1));
+        assert!(!image.is_hit(Point { x: 3, y: 1 }, 0));
+        assert!(!image.is_hit(Point { x: -1, y: 1 }, 0));
+    }
+
+    #[test]
+    fn test_rotated_bilinear_edges() {
+        // A red pixel at the end of the first row and a blue pixel at the
+        // start of the second row, which are not next to each other.
+        let mut image = Image::empty(Size {
+            width: 3,
+            height: 2,
+        });
+        image.set_pixel_color(Color::RED, Point { x: 2, y: 0 });
+        image.set_pixel_color(Color::BLUE, Point { x: 0, y: 1 });
+
+        let angle = std::f32::consts::PI * 0.1;
+        let (rotated, _) = image.rotated(angle, Point { x: 1.5, y: 1.0 }, Sampling::Bilinear);
+        for y in 0..rotated.size.height as i32 {
+            for x in 0..rotated.size.width as i32 {
+                let color = rotated.pixel_color(Point { x, y }).unwrap();
+                assert!(color.red == 0 || color.blue == 0, "{color:?} at {x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_luma_matte() {
+        let size = Size {
+            width: 5,
+            height: 2,
+        };
+        let mut matte = Image::empty(size);
+        for y in 0..2 {
+            for x in 0..5 {
+                let value = (x * 0x40).min(0xff) as u8;
+                let color = Color {
+                    red: value,
+                    green: value,
+                    blue: value,
+                    alpha: 0xff,
+                };
+                matte.set_pixel_color(color, Point { x, y });
+            }
+        }
+        let original = Image::color(&Color::RED, size);
+
+        let mut image = original.clone();
+        image.apply_luma_matte(&matte, false).unwrap();
+        let alphas: Vec<u8> = (0..5)
+            .map(|x| image.pixel_color(Point { x, y: 1 }).unwrap().alpha)
+            .collect();
+        assert_eq!(alphas, vec![0x00, 0x40, 0x80, 0xc0, 0xff]);
+        assert_eq!(
+            image.pixel_color(Point { x: 2, y: 0 }),
+            Some(Color::RED.with_alpha(0x80))
+        );
+
+        let mut image = original.clone();
+        image.apply_luma_matte(&matte, true).unwrap();
+        let alphas: Vec<u8> = (0..5)
+            .map(|x| image.pixel_color(Point { x, y: 0 }).unwrap().alpha)
+            .collect();
+        assert_eq!(alphas, vec![0xff, 0xbf, 0x7f, 0x3f, 0x00]);
+
+        let small_matte = Image::empty(Size {
+            width: 1,
+            height: 1,
+        });
+        assert!(image.apply_luma_matte(&small_matte, false).is_err());
+    }
+
+    #[test]
+    fn test_set_pixel_if() {
+        let mut image = Image::color(
+            &Color::from_rgb_u32(0x808080),
+            Size {
+                width: 2,
+                height: 2,
+            },
+        );
+        image.set_pixel_color(Color::RED, Point { x: 1, y: 1 });
+        let expected = Color::from_rgb_u32(0x828282);
+
+        image.set_pixel_if(Point { x: 0, y: 0 }, &Color::BLUE, &expected, 2);
+        image.set_pixel_if(Point { x: 1, y: 0 }, &Color::BLUE, &expected, 1);
+        image.set_pixel_if(Point { x: 1, y: 1 }, &Color::BLUE, &expected, 2);
+        image.set_pixel_if(Point { x: 2, y: 0 }, &Color::BLUE, &expected, 2);
+
+        assert_eq!(image.pixel_color(Point { x: 0, y: 0 }), Some(Color::BLUE));
+        assert_eq!(
+            image.pixel_color(Point { x: 1, y: 0 }),
+            Some(Color::from_rgb_u32(0x808080))
+        );
+        assert_eq!(
+            image.pixel_color(Point { x: 0, y: 1 }),
+            Some(Color::from_rgb_u32(0x808080))
+        );
+        assert_eq!(image.pixel_color(Point { x: 1, y: 1 }), Some(Color::RED));
+    }
+
+    #[test]
+    fn test_cropped() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+        let region = Rect::new(3, 5, 10, 7);
+
+        let cropped = image.cropped(region).unwrap();
+        assert_eq!(cropped, image.subimage(region).unwrap());
+
+        // The region is clamped to the image.
+        let cropped = image.cropped(Rect::new(-4, -2, 10, 7)).unwrap();
+        assert_eq!(cropped, image.subimage(Rect::new(0, 0, 6, 5)).unwrap());
+        let far_corner = Rect::new(
+            image.size.width as i32 - 3,
+            image.size.height as i32 - 2,
+            10,
+            10,
+        );
+        let cropped = image.cropped(far_corner).unwrap();
+        assert_eq!(
+            cropped.size,
+            Size {
+                width: 3,
+                height: 2
+            }
+        );
+
+        assert!(image.cropped(Rect::new(-10, 0, 10, 10)).is_err());
+        assert!(image.cropped(Rect::new(2, 2, 0, 4)).is_err());
+    }
+
+    #[test]
+    fn test_nonempty_tiles() {
+        let mut image = Image::empty(Size {
+            width: 10,
+            height: 7,
+        });
+        image.set_pixel_color(Color::RED, Point { x: 9, y: 5 });
+        image.set_pixel_color(Color::BLUE.with_alpha(1), Point { x: 8, y: 6 });
+        let tile_size = Size {
+            width: 4,
+            height: 4,
+        };
+
+        let tiles = image.nonempty_tiles(tile_size);
+
+        assert_eq!(tiles.len(), 1);
+        let (position, tile) = &tiles[0];
+        assert_eq!(*position, Point { x: 2, y: 1 });
+        assert_eq!(
+            tile.size,
+            Size {
+                width: 2,
+                height: 3
+            }
+        );
+        assert_eq!(tile.pixel_color(Point { x: 1, y: 1 }), Some(Color::RED));
+        assert_eq!(
+            tile.pixel_color(Point { x: 0, y: 2 }),
+            Some(Color::BLUE.with_alpha(1))
+        );
+
+        assert!(Image::empty(image.size)
+            .nonempty_tiles(tile_size)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tiled() {
+        let mut tile = Image::empty(Size {
+            width: 2,
+            height: 2,
+        });
+        let tile_colors = [[Color::RED, Color::GREEN], [Color::BLUE, Color::WHITE]];
+        for (y, row) in tile_colors.iter().enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                let location = Point {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                tile.set_pixel_color(color.clone(), location);
+            }
+        }
+
+        let image = Image::tiled(
+            &tile,
+            Size {
+                width: 5,
+                height: 5,
+            },
+        );
+
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = tile_colors[y as usize % 2][x as usize % 2].clone();
+                assert_eq!(image.pixel_color(Point { x, y }), Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let image = Image::open("tests/images/avatar.png").unwrap();
+        assert_eq!(image.content_hash(), image.clone().content_hash());
+
+        // Row padding doesn’t affect the hash.
+        let mut padded = Image::empty_aligned(image.size, 64);
+        padded.draw_image_over(&image, Point::zero());
+        assert_ne!(padded.bytes_per_row, image.bytes_per_row);
+        assert_eq!(padded.content_hash(), image.content_hash());
+
+        let mut changed = image.clone();
+        changed.set_pixel_color(Color::from_rgb_u32(0x123456), Point { x: 0, y: 0 });
+        assert_ne!(changed.content_hash(), image.content_hash());
+    }
+
+    #[test]
+    fn test_rows_and_pixels() {
+        let size = Size {
+            width: 2,
+            height: 2,
+        };
+        let mut image = Image::empty_aligned(size, 16);
+        image.set_pixel_color(Color::RED, Point { x: 0, y: 0 });
+        image.set_pixel_color(Color::GREEN, Point { x: 1, y: 0 });
+        image.set_pixel_color(Color::BLUE, Point { x: 0, y: 1 });
+        image.set_pixel_color(Color::WHITE, Point { x: 1, y: 1 });
+        assert_eq!(image.bytes_per_row, 16);
+
+        let rows: Vec<&[u8]> = image.rows().collect();
+        assert_eq!(
+            rows,
+            vec![
+                &[0xff, 0, 0, 0xff, 0, 0xff, 0, 0xff][..],
+                &[0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff][..],
+            ]
+        );
+
+        let pixels: Vec<Color> = image.pixels().collect();
+        assert_eq!(
+            pixels,
+            vec![Color::RED, Color::GREEN, Color::BLUE, Color::WHITE]
+        );
+    }
+
+    #[test]
+    fn test_scaled_down() {
+        let mut image = Image::empty(Size {
+            width: 6,
+            height: 6,
+        });
+        for y in 0..6 {
+            for x in 0..6 {
+                let color = Color::from_rgb_u32(x * 0x10 + y);
+                image.set_pixel_color(color, Point { x, y });
+            }
+        }
+
+        let scaled = image.scaled_down(3);
+
+        assert_eq!(
+            scaled.size,
+            Size {
+                width: 2,
+                height: 2
+            }
+        );
+        for y in 0..2 {
+            for x in 0..2 {
+                let expected = Color::from_rgb_u32(x as u32 * 0x30 + y as u32 * 3);
+                assert_eq!(scaled.pixel_color(Point { x, y }), Some(expected));
+            }
+        }
+
+        assert_eq!(image.scaled_down(1), image);
+        let scaled = image.scaled_down(4);
+        assert_eq!(
+            scaled.size,
+            Size {
+                width: 2,
+                height: 2
+            }
+        );
+        assert_eq!(
+            scaled.pixel_color(Point { x: 1, y: 1 }),
+            Some(Color::from_rgb_u32(0x44))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_scaled_down_by_zero() {
+        Image::empty(Size {
+            width: 2,
+            height: 2,
+        })
+        .scaled_down(0);
+    }
+
+    #[test]
+    fn test_color() {
+        let size = Size {
+            width: 3,
+            height: 2,
+        };
+        let color = Color::from_rgba_u32(0x12345678);
+        let image = Image::color(&color, size);
+
+        assert_eq!(image.size, size);
+        assert_eq!(image.bytes_per_row, 12);
+        assert_eq!(image.data.len(), 3 * 2 * 4);
+        assert!(image
+            .data
+            .chunks_exact(4)
+            .all(|pixel| pixel == [0x12, 0x34, 0x56, 0x78]));
+        assert!(image.pixels().all(|pixel| pixel == color));
+
+        for size in [
+            Size {
+                width: 0,
+                height: 0,
+            },
+            Size {
+                width: 0,
+                height: 5,
+            },
+            Size {
+                width: 5,
+                height: 0,
+            },
+        ] {
+            let image = Image::color(&color, size);
+            assert!(image.data.is_empty());
+            assert_eq!(image.bytes_per_row, size.width * 4);
+            assert_eq!(image, Image::empty(size));
+        }
+    }
 }